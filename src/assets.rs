@@ -0,0 +1,116 @@
+use crate::constants::{ASSETS_ENV, GAME_NAME};
+use find_folder::Search;
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub enum AssetError {
+    NotFound {
+        name: String,
+        searched: Vec<PathBuf>,
+    },
+    Load {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::NotFound { name, searched } => write!(
+                f,
+                "asset {} not found in {}",
+                name,
+                searched
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            AssetError::Load { path, message } => {
+                write!(f, "failed to load {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+// Per-user directory for the game's data, following each platform's convention
+
+pub fn data_dir() -> Option<PathBuf> {
+    let base: PathBuf = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+    } else {
+        match env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+        }
+    };
+
+    Some(base.join(GAME_NAME))
+}
+
+// Folders that may hold an `assets` tree, searched in order: the environment variable, next to
+// the executable, the user data directory, then the source tree when run through cargo
+
+pub struct Assets {
+    pub search_paths: Vec<PathBuf>,
+}
+
+impl Default for Assets {
+    fn default() -> Self {
+        let mut search_paths: Vec<PathBuf> = Vec::new();
+
+        if let Some(dir) = env::var_os(ASSETS_ENV) {
+            search_paths.push(PathBuf::from(dir));
+        }
+
+        if let Some(dir) = env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            search_paths.push(dir.join("assets"));
+        }
+
+        if let Some(dir) = data_dir() {
+            search_paths.push(dir.join("assets"));
+        }
+
+        if let Ok(dir) = Search::ParentsThenKids(3, 3).for_folder("assets") {
+            search_paths.push(dir);
+        }
+
+        Assets { search_paths }
+    }
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Assets {
+            ..Default::default()
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Result<PathBuf, AssetError> {
+        self.find_where(name, Path::is_file)
+    }
+
+    // A folder of assets, like the levels
+
+    pub fn find_dir(&self, name: &str) -> Result<PathBuf, AssetError> {
+        self.find_where(name, Path::is_dir)
+    }
+
+    fn find_where(&self, name: &str, exists: fn(&Path) -> bool) -> Result<PathBuf, AssetError> {
+        self.search_paths
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| exists(path))
+            .ok_or_else(|| AssetError::NotFound {
+                name: name.to_string(),
+                searched: self.search_paths.clone(),
+            })
+    }
+}
@@ -1,18 +1,164 @@
+use crate::collider::{vertex_bounds, Collider};
+use crate::color::{with_alpha, ColorChannels, CHANNEL_BLOCK};
+use crate::constants::BLOCK_HITBOX_SCALE;
+use crate::crash_report::Contact;
+use crate::game_object::{GameObject, Interaction};
+use crate::player::Player;
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::side::Side;
+use crate::util::parse_nums;
 use crate::vector::Vector;
+use opengl_graphics::GlGraphics;
 
 #[derive(PartialEq)]
 pub struct Block {
     pub pos: Vector,
     pub size: Vector,
-    pub on_screen: bool,
+    pub angle: f32,
+    pub props: Properties,
 }
 
 impl Block {
-    pub fn new(pos: Vector, size: Vector) -> Self {
+    pub fn new(pos: Vector, size: Vector, props: Properties) -> Self {
         Block {
             pos,
             size,
-            on_screen: false,
+            angle: 0.0,
+            props,
         }
     }
+
+    // 1,x,y,w,h
+
+    pub fn parse(fields: &[&str], props: Properties) -> Self {
+        let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+        Block::new(
+            Vector::new(nums[0], nums[1]),
+            Vector::new(nums[2], nums[3]),
+            props,
+        )
+    }
+
+    pub fn axis_aligned(&self) -> bool {
+        let r: f32 = self.angle.rem_euclid(90.0);
+        !(0.01..=89.99).contains(&r)
+    }
+
+    // Only meaningful while the block is axis aligned
+
+    pub fn hitbox_bounds(&self) -> Rect {
+        let scale: f32 = self.props.hitbox.unwrap_or(BLOCK_HITBOX_SCALE);
+        let center: Vector = self.center();
+        let quarter_turns: i32 = (self.angle / 90.0).round() as i32;
+        let size: Vector = if quarter_turns.rem_euclid(2) == 1 {
+            Vector::new(self.size.y, self.size.x) * scale
+        } else {
+            self.size * scale
+        };
+
+        Rect::centered(center, size)
+    }
+
+    pub fn vertices(&self) -> [[f64; 2]; 4] {
+        self.scaled_vertices(1.0)
+    }
+
+    pub fn hitbox_vertices(&self) -> [[f64; 2]; 4] {
+        self.scaled_vertices(self.props.hitbox.unwrap_or(BLOCK_HITBOX_SCALE))
+    }
+
+    fn scaled_vertices(&self, scale: f32) -> [[f64; 2]; 4] {
+        let center: Vector = self.center();
+        Rect::centered(center, self.size * scale)
+            .corners()
+            .map(|corner| corner.rotate_about(center, self.angle).to_vertex())
+    }
+
+    // Which face of the block `point` is closest to facing, in the block's own rotated frame
+
+    pub fn side_facing(&self, point: Vector) -> Side {
+        let center: Vector = self.center();
+        let local: Vector = point.rotate_about(center, -self.angle) - center;
+        let dx: f32 = local.x / self.size.x;
+        let dy: f32 = local.y / self.size.y;
+
+        if dx.abs() > dy.abs() {
+            if dx < 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            }
+        } else if dy < 0.0 {
+            Side::Top
+        } else {
+            Side::Bottom
+        }
+    }
+}
+
+impl GameObject for Block {
+    fn name(&self) -> &'static str {
+        "block"
+    }
+
+    fn props(&self) -> &Properties {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut Properties {
+        &mut self.props
+    }
+
+    fn interaction(&self) -> Interaction {
+        Interaction::Solid
+    }
+
+    // Only axis-aligned blocks are rectangles that can be swept and landed on
+
+    fn collider(&self) -> Collider {
+        if self.axis_aligned() {
+            Collider::Rect(self.hitbox_bounds())
+        } else {
+            Collider::Polygon(self.hitbox_vertices().to_vec())
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        vertex_bounds(&self.vertices())
+    }
+
+    fn center(&self) -> Vector {
+        Rect::new(self.pos, self.size).center()
+    }
+
+    fn translate(&mut self, delta: Vector) {
+        self.pos += delta;
+    }
+
+    fn rotate(&mut self, pivot: Option<Vector>, degrees: f32) {
+        if let Some(pivot) = pivot {
+            let center: Vector = self.center();
+            self.translate(center.rotate_about(pivot, degrees) - center);
+        }
+
+        self.angle += degrees;
+    }
+
+    fn render(&self, colors: &ColorChannels, transform: [[f64; 3]; 2], gl: &mut GlGraphics) {
+        graphics::polygon(
+            with_alpha(
+                colors.get(self.props.color_or(CHANNEL_BLOCK)),
+                self.props.alpha,
+            ),
+            &self.vertices(),
+            transform,
+            gl,
+        );
+    }
+
+    fn contact(&self, player: &Player) -> Contact {
+        Contact::Side(self.side_facing(player.pos))
+    }
 }
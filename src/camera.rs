@@ -0,0 +1,82 @@
+use crate::constants::{CAMERA_DEAD_ZONE, HEIGHT, PLAYER_SCREEN_X, WIDTH};
+use crate::player::Player;
+use crate::vector::Vector;
+
+// World point shown at the middle of the screen; zoom scales around that point
+
+pub struct Camera {
+    pub center: Vector,
+    pub follow_y: f32,
+    pub zoom: f32,
+    pub offset: Vector,
+    pub lock: Vector,
+    pub lock_blend: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            center: Vector::new(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0),
+            follow_y: HEIGHT as f32 / 2.0,
+            zoom: 1.0,
+            offset: Vector::new(0.0, 0.0),
+            lock: Vector::new(0.0, 0.0),
+            lock_blend: 0.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn new() -> Self {
+        Camera {
+            ..Default::default()
+        }
+    }
+
+    // Vertically the camera only moves once the player leaves the dead zone around its center
+
+    pub fn follow(&mut self, player: &Player) {
+        if player.pos.y > self.follow_y + CAMERA_DEAD_ZONE {
+            self.follow_y = player.pos.y - CAMERA_DEAD_ZONE;
+        } else if player.pos.y < self.follow_y - CAMERA_DEAD_ZONE {
+            self.follow_y = player.pos.y + CAMERA_DEAD_ZONE;
+        }
+
+        let follow: Vector = Vector::new(
+            player.pos.x - PLAYER_SCREEN_X + (WIDTH as f32 / 2.0) + self.offset.x,
+            self.follow_y + self.offset.y,
+        );
+
+        self.center = Vector::new(
+            follow.x + (self.lock.x - follow.x) * self.lock_blend,
+            follow.y + (self.lock.y - follow.y) * self.lock_blend,
+        );
+    }
+
+    pub fn transform(&self, transform: [[f64; 3]; 2]) -> [[f64; 3]; 2] {
+        use graphics::Transformed;
+
+        transform
+            .trans(WIDTH as f64 / 2.0, HEIGHT as f64 / 2.0)
+            .zoom(self.zoom as f64)
+            .trans(-self.center.x as f64, -self.center.y as f64)
+    }
+
+    pub fn screen_to_world(&self, point: Vector) -> Vector {
+        (point - Vector::new(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0)) / self.zoom + self.center
+    }
+
+    // Visible world area as (left, top, right, bottom)
+
+    pub fn view(&self) -> [f32; 4] {
+        let half_width: f32 = WIDTH as f32 / 2.0 / self.zoom;
+        let half_height: f32 = HEIGHT as f32 / 2.0 / self.zoom;
+
+        [
+            self.center.x - half_width,
+            self.center.y - half_height,
+            self.center.x + half_width,
+            self.center.y + half_height,
+        ]
+    }
+}
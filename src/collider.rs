@@ -0,0 +1,38 @@
+use crate::rect::Rect;
+use crate::util::polygon_rect;
+use crate::vector::Vector;
+
+pub enum Collider {
+    Rect(Rect),
+    Triangle([[f64; 2]; 3]),
+    Circle { center: Vector, radius: f32 },
+    Polygon(Vec<[f64; 2]>),
+}
+
+impl Collider {
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        match self {
+            Collider::Rect(own) => own.intersects(rect),
+            Collider::Triangle(vertices) => polygon_rect(vertices, rect),
+            Collider::Circle { center, radius } => center.dist(rect.clamp(*center)) <= *radius,
+            Collider::Polygon(vertices) => polygon_rect(vertices, rect),
+        }
+    }
+
+    // Axis-aligned bounding box
+
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Collider::Rect(own) => *own,
+            Collider::Triangle(vertices) => vertex_bounds(vertices),
+            Collider::Circle { center, radius } => {
+                Rect::centered(*center, Vector::new(radius * 2.0, radius * 2.0))
+            }
+            Collider::Polygon(vertices) => vertex_bounds(vertices),
+        }
+    }
+}
+
+pub fn vertex_bounds(vertices: &[[f64; 2]]) -> Rect {
+    Rect::around(vertices.iter().map(|&v| Vector::from_vertex(v)))
+}
@@ -0,0 +1,90 @@
+use crate::collider::Collider;
+use crate::color::ColorChannels;
+use crate::game_object::{GameObject, Interaction};
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::util::parse_nums;
+use crate::vector::Vector;
+use opengl_graphics::GlGraphics;
+
+// Invisible rectangle that only exists to be tested against other collision blocks by ID
+
+pub struct CollisionBlock {
+    pub pos: Vector,
+    pub size: Vector,
+    pub id: u32,
+    pub props: Properties,
+}
+
+impl CollisionBlock {
+    pub fn new(pos: Vector, size: Vector, id: u32, props: Properties) -> Self {
+        CollisionBlock {
+            pos,
+            size,
+            id,
+            props,
+        }
+    }
+
+    // 8,x,y,w,h,id
+
+    pub fn parse(fields: &[&str], props: Properties) -> Self {
+        let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+        CollisionBlock::new(
+            Vector::new(nums[0], nums[1]),
+            Vector::new(nums[2], nums[3]),
+            fields[5].parse::<u32>().unwrap(),
+            props,
+        )
+    }
+}
+
+impl GameObject for CollisionBlock {
+    fn name(&self) -> &'static str {
+        "collision block"
+    }
+
+    fn props(&self) -> &Properties {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut Properties {
+        &mut self.props
+    }
+
+    fn interaction(&self) -> Interaction {
+        Interaction::Passive
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::Rect(Rect::new(self.pos, self.size))
+    }
+
+    fn bounds(&self) -> Rect {
+        self.collider().bounds()
+    }
+
+    fn center(&self) -> Vector {
+        Rect::new(self.pos, self.size).center()
+    }
+
+    fn translate(&mut self, delta: Vector) {
+        self.pos += delta;
+    }
+
+    // Collision blocks stay axis aligned, only their position follows the pivot
+
+    fn rotate(&mut self, pivot: Option<Vector>, degrees: f32) {
+        if let Some(pivot) = pivot {
+            let center: Vector = self.center();
+            self.translate(center.rotate_about(pivot, degrees) - center);
+        }
+    }
+
+    fn render(&self, _colors: &ColorChannels, _transform: [[f64; 3]; 2], _gl: &mut GlGraphics) {}
+
+    fn collision_id(&self) -> Option<u32> {
+        Some(self.id)
+    }
+}
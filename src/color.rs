@@ -0,0 +1,64 @@
+use crate::constants::{BG_COLOR, BLOCK_COLOR, GROUND_COLOR, ORB_COLOR, PLAYER_COLOR, SPIKE_COLOR};
+use std::collections::HashMap;
+
+pub const CHANNEL_BG: &str = "bg";
+pub const CHANNEL_GROUND: &str = "ground";
+pub const CHANNEL_PLAYER: &str = "player";
+pub const CHANNEL_BLOCK: &str = "block";
+pub const CHANNEL_SPIKE: &str = "spike";
+pub const CHANNEL_ORB: &str = "orb";
+
+pub struct ColorChannels {
+    pub channels: HashMap<String, [f32; 4]>,
+}
+
+impl Default for ColorChannels {
+    fn default() -> Self {
+        let mut channels: HashMap<String, [f32; 4]> = HashMap::new();
+
+        channels.insert(CHANNEL_BG.to_string(), BG_COLOR);
+        channels.insert(CHANNEL_GROUND.to_string(), GROUND_COLOR);
+        channels.insert(CHANNEL_PLAYER.to_string(), PLAYER_COLOR);
+        channels.insert(CHANNEL_BLOCK.to_string(), BLOCK_COLOR);
+        channels.insert(CHANNEL_SPIKE.to_string(), SPIKE_COLOR);
+        channels.insert(CHANNEL_ORB.to_string(), ORB_COLOR);
+
+        ColorChannels { channels }
+    }
+}
+
+impl ColorChannels {
+    pub fn new() -> Self {
+        ColorChannels {
+            ..Default::default()
+        }
+    }
+
+    // Unknown channels render white so a typo in a level file is visible rather than invisible
+
+    pub fn get(&self, channel: &str) -> [f32; 4] {
+        *self.channels.get(channel).unwrap_or(&[1.0, 1.0, 1.0, 1.0])
+    }
+
+    pub fn set(&mut self, channel: &str, color: [f32; 4]) {
+        self.channels.insert(channel.to_string(), color);
+    }
+}
+
+pub fn lerp_color(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    [
+        from[0] + (to[0] - from[0]) * t,
+        from[1] + (to[1] - from[1]) * t,
+        from[2] + (to[2] - from[2]) * t,
+        from[3] + (to[3] - from[3]) * t,
+    ]
+}
+
+pub fn with_alpha(color: [f32; 4], alpha: f32) -> [f32; 4] {
+    [
+        color[0],
+        color[1],
+        color[2],
+        color[3] * alpha.clamp(0.0, 1.0),
+    ]
+}
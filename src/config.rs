@@ -0,0 +1,93 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Minimal INI file shared by the game's config files. Lines are `[section]` headers or
+// `key = value` pairs, and `#` or `;` start a comment line. Pairs before the first header belong
+// to the unnamed section ""
+
+pub struct Config {
+    pub sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Self {
+        let mut sections: Vec<(String, Vec<(String, String)>)> = vec![(String::new(), Vec::new())];
+
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                sections.push((name.trim().to_string(), Vec::new()));
+            } else if let Some((key, value)) = line.split_once('=') {
+                sections
+                    .last_mut()
+                    .unwrap()
+                    .1
+                    .push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        Config { sections }
+    }
+
+    // A missing file isn't an error, every config file is optional
+
+    pub fn load(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|text| Config::parse(&text))
+    }
+
+    pub fn has_section(&self, section: &str) -> bool {
+        self.sections.iter().any(|(name, _)| name == section)
+    }
+
+    // Later pairs win when a key is repeated
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .iter()
+            .filter(|(name, _)| name == section)
+            .flat_map(|(_, pairs)| pairs.iter())
+            .rfind(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    // Writes to a temporary file next to `path` and renames it over, so a crash mid-write
+    // leaves the old file intact
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let temp: PathBuf = path.with_extension("tmp");
+
+        fs::write(&temp, self.to_string())?;
+        fs::rename(&temp, path)
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (name, pairs)) in self.sections.iter().enumerate() {
+            if !name.is_empty() {
+                if i > 0 {
+                    writeln!(f)?;
+                }
+
+                writeln!(f, "[{}]", name)?;
+            }
+
+            for (key, value) in pairs {
+                writeln!(f, "{} = {}", key, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
+// Size of the view in world units, which levels are laid out in. It is scaled to fit the window
 pub const WIDTH: u32 = 1920;
 pub const HEIGHT: u32 = 1080;
 
 pub const BG_COLOR: [f32; 4] = [0.25, 0.25, 0.25, 1.0];
 pub const PLAYER_COLOR: [f32; 4] = [0.0, 1.0, 1.0, 1.0];
 pub const GROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-pub const GROUND_COLOR_TRANSPARENT: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
 pub const BLOCK_COLOR: [f32; 4] = GROUND_COLOR;
 pub const SPIKE_COLOR: [f32; 4] = GROUND_COLOR;
 pub const ORB_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
@@ -12,11 +12,40 @@ pub const ORB_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
 pub const GROUND_Y_NORMAL: f32 = HEIGHT as f32 - (HEIGHT as f32 * 0.15);
 pub const GROUND_Y_FLIP: f32 = HEIGHT as f32 * 0.15;
 
+pub const PLAYER_SCREEN_X: f32 = WIDTH as f32 * 0.2;
+pub const CAMERA_DEAD_ZONE: f32 = HEIGHT as f32 * 0.35;
+pub const FALL_MARGIN: f32 = HEIGHT as f32 / 2.0; // How far past the level an open section kills
+
 pub const PLAYER_SIZE: u32 = 50;
+pub const PLAYER_SOLID_SCALE: f32 = 0.3;
 pub const PLAYER_SPEED: f32 = 10.386 * 60.0;
 
+pub const BLOCK_HITBOX_SCALE: f32 = 1.0;
+pub const SPIKE_HITBOX_SCALE: f32 = 0.4;
+pub const ORB_HITBOX_SCALE: f32 = 1.0;
+
+pub const INDEX_CELL_WIDTH: f32 = 400.0;
+
+pub const EDITOR_GRID: f32 = 50.0;
+
 pub const GRAVITY: f32 = 0.575 * 60.0;
+pub const JUMP_VELOCITY: f32 = GRAVITY * GRAVITY;
+pub const JUMP_ARC: f32 = 250.0;
+
+pub const PHYSICS_FILE: &str = "physics.ini";
+
+pub const GAME_NAME: &str = "gdrs";
+pub const ASSETS_ENV: &str = "GDRS_ASSETS"; // Overrides where assets are looked up first
+pub const PROGRESS_FILE: &str = "progress.ini"; // Saved in the user data directory
+pub const SETTINGS_FILE: &str = "settings.ini"; // Saved in the user data directory
+pub const FONT_FILE: &str = "CaskaydiaCoveNerdFontCompleteRegular.otf";
 
 pub const FPS: u64 = 60;
+pub const UPS: u64 = 120;
+pub const CHECKPOINT_INTERVAL: u64 = UPS * 2; // Practice mode places a checkpoint this often
+pub const RETRY_DELAY: f32 = 1.0; // Default seconds a crash stays on screen before auto retry
+pub const DEATH_EFFECT_TIME: f32 = 0.5; // Seconds the player takes to burst after a crash
+pub const DEATH_PIECES: u32 = 8;
 
-pub const SELECTED_LEVEL: &str = "level_1"; // Change this based on file names in levels/
+pub const LEVELS_DIR: &str = "levels";
+pub const SELECTED_LEVEL: &str = "level_1"; // Level for --headless and --edit when none is given
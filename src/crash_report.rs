@@ -0,0 +1,54 @@
+use crate::side::Side;
+use crate::vector::Vector;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum Contact {
+    Side(Side),
+    SpikeEdge(usize),
+    Inside,
+    // Fell past the level through an open floor or ceiling
+    OutOfBounds,
+}
+
+// Why the player died, so level designers can track down unfair hitboxes
+
+pub struct CrashReport {
+    pub kind: &'static str,
+    pub line: usize,
+    pub contact: Contact,
+    pub tick: u64,
+    pub pos: Vector,
+}
+
+impl CrashReport {
+    pub fn new(kind: &'static str, line: usize, contact: Contact, tick: u64, pos: Vector) -> Self {
+        CrashReport {
+            kind,
+            line,
+            contact,
+            tick,
+            pos,
+        }
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let contact: String = match &self.contact {
+            Contact::Side(Side::Top) => "top side".to_string(),
+            Contact::Side(Side::Bottom) => "bottom side".to_string(),
+            Contact::Side(Side::Left) => "left side".to_string(),
+            Contact::Side(Side::Right) => "right side".to_string(),
+            Contact::SpikeEdge(edge) => format!("edge {}", edge),
+            Contact::Inside => "inside".to_string(),
+            Contact::OutOfBounds => "out of bounds".to_string(),
+        };
+
+        write!(
+            f,
+            "{} on line {}, {}, tick {} at ({:.1}, {:.1})",
+            self.kind, self.line, contact, self.tick, self.pos.x, self.pos.y
+        )
+    }
+}
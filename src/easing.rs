@@ -0,0 +1,81 @@
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    BounceIn,
+    BounceOut,
+    ElasticIn,
+    ElasticOut,
+}
+
+impl Easing {
+    pub fn from_name(name: &str) -> Self {
+        match name.trim() {
+            "ease_in" => Easing::EaseIn,
+            "ease_out" => Easing::EaseOut,
+            "ease_in_out" => Easing::EaseInOut,
+            "bounce_in" => Easing::BounceIn,
+            "bounce" | "bounce_out" => Easing::BounceOut,
+            "elastic_in" => Easing::ElasticIn,
+            "elastic" | "elastic_out" => Easing::ElasticOut,
+            _ => Easing::Linear,
+        }
+    }
+
+    // https://easings.net
+
+    pub fn apply(&self, t: f32) -> f32 {
+        let t: f32 = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t.powi(2),
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(2),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t.powi(2)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::ElasticIn => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    -(2.0_f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+                }
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    let n: f32 = 7.5625;
+    let d: f32 = 2.75;
+
+    if t < 1.0 / d {
+        n * t.powi(2)
+    } else if t < 2.0 / d {
+        let t: f32 = t - 1.5 / d;
+        n * t.powi(2) + 0.75
+    } else if t < 2.5 / d {
+        let t: f32 = t - 2.25 / d;
+        n * t.powi(2) + 0.9375
+    } else {
+        let t: f32 = t - 2.625 / d;
+        n * t.powi(2) + 0.984375
+    }
+}
@@ -0,0 +1,663 @@
+use crate::assets::AssetError;
+use crate::camera::Camera;
+use crate::color::CHANNEL_BG;
+use crate::constants::{EDITOR_GRID, GROUND_Y_NORMAL, HEIGHT, PLAYER_SIZE, WIDTH};
+use crate::game::{level_path, read_lines, Game};
+use crate::rect::Rect;
+use crate::text::{Align, Text};
+use crate::vector::Vector;
+use graphics::Viewport;
+use opengl_graphics::GlGraphics;
+use piston::{Button, ButtonState, Key, MouseButton};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+// Size of the marker drawn for triggers and sections, which only have an x position
+const MARKER_SIZE: f32 = 30.0;
+
+const TEMPLATE_NAMES: [&str; 7] = [
+    "block",
+    "spike",
+    "ceiling spike",
+    "orb",
+    "collision block",
+    "move trigger",
+    "start position",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Build,
+    Edit,
+}
+
+// One line of the level file. Fields stay as text so saving gives back every property and
+// trigger setting exactly as it was loaded
+
+#[derive(Clone)]
+pub struct Entry {
+    pub fields: Vec<String>,
+}
+
+impl Entry {
+    pub fn parse(line: &str) -> Self {
+        Entry {
+            fields: line.split(',').map(|x| x.to_string()).collect(),
+        }
+    }
+
+    pub fn line(&self) -> String {
+        self.fields.join(",")
+    }
+
+    fn kind(&self) -> u32 {
+        self.fields[0].parse::<u32>().unwrap_or(0)
+    }
+
+    fn number(&self, i: usize) -> f32 {
+        self.fields[i].parse::<f32>().unwrap()
+    }
+
+    fn set_number(&mut self, i: usize, value: f32) {
+        self.fields[i] = format!("{}", value);
+    }
+
+    fn has_y(&self) -> bool {
+        matches!(self.kind(), 1 | 2 | 3 | 8 | 17)
+    }
+
+    // Triggers and sections are drawn as a marker in the band at the top of the view
+
+    fn is_marker(&self) -> bool {
+        !matches!(self.kind(), 0 | 1 | 2 | 3 | 8 | 17)
+    }
+
+    fn anchor(&self) -> Vector {
+        Vector::new(
+            self.number(1),
+            if self.has_y() { self.number(2) } else { 0.0 },
+        )
+    }
+
+    // Area that picks the entry with the mouse. Metadata lines have none
+
+    fn bounds(&self, band: &Rect) -> Option<Rect> {
+        match self.kind() {
+            0 => None,
+            1 | 8 => Some(Rect::new(
+                Vector::new(self.number(1), self.number(2)),
+                Vector::new(self.number(3), self.number(4)),
+            )),
+            2 => {
+                let size: Vector = Vector::new(self.number(3), self.number(4));
+                let flip: bool = self.fields[5] == "true";
+
+                Some(Rect::new(
+                    Vector::new(
+                        self.number(1) - (size.x / 2.0),
+                        if flip {
+                            self.number(2) - size.y
+                        } else {
+                            self.number(2)
+                        },
+                    ),
+                    size,
+                ))
+            }
+            3 => Some(Rect::centered(
+                Vector::new(self.number(1), self.number(2)),
+                Vector::new(self.number(3), self.number(3)),
+            )),
+            17 => Some(Rect::centered(
+                Vector::new(self.number(1), self.number(2)),
+                Vector::new(PLAYER_SIZE as f32, PLAYER_SIZE as f32) * self.number(6),
+            )),
+            _ => Some(Rect::new(
+                Vector::new(self.number(1) - (MARKER_SIZE / 2.0), band.pos.y),
+                Vector::new(MARKER_SIZE, band.size.y),
+            )),
+        }
+    }
+
+    fn translate(&mut self, delta: Vector) {
+        self.set_number(1, self.number(1) + delta.x);
+
+        if self.has_y() {
+            self.set_number(2, self.number(2) + delta.y);
+        }
+    }
+}
+
+// Level line for a new object whose grid cell has its top left corner at `cell`
+
+fn template_line(template: usize, cell: Vector) -> String {
+    let (x, y, g): (f32, f32, f32) = (cell.x, cell.y, EDITOR_GRID);
+
+    match template {
+        0 => format!("1,{},{},{},{}", x, y, g, g),
+        1 => format!("2,{},{},{},{},true", x + g / 2.0, y + g, g, g),
+        2 => format!("2,{},{},{},{},false", x + g / 2.0, y, g, g),
+        3 => format!("3,{},{},{}", x + g / 2.0, y + g / 2.0, g),
+        4 => format!("8,{},{},{},{},1", x, y, g, g),
+        5 => format!("4,{},1,0,{},0.5,ease_in_out", x + g / 2.0, -g * 2.0),
+        _ => format!("17,{},{},cube,normal,1,1", x + g / 2.0, y + g / 2.0),
+    }
+}
+
+// Top left corner of the grid cell under `point`. Rows line up with the default floor so
+// placed objects sit flush on the ground
+
+fn cell(point: Vector) -> Vector {
+    Vector::new(
+        (point.x / EDITOR_GRID).floor() * EDITOR_GRID,
+        GROUND_Y_NORMAL + ((point.y - GROUND_Y_NORMAL) / EDITOR_GRID).floor() * EDITOR_GRID,
+    )
+}
+
+pub struct Editor {
+    // File the level was loaded from, saving writes back to it
+    path: PathBuf,
+    pub entries: Vec<Entry>,
+    // Indices into `entries`, kept sorted
+    pub selected: Vec<usize>,
+    clipboard: Vec<Entry>,
+    history: Vec<Vec<Entry>>,
+    future: Vec<Vec<Entry>>,
+    mode: Mode,
+    template: usize,
+    camera: Camera,
+    mouse: Vector,
+    ctrl: bool,
+    shift: bool,
+    drag: Option<Vector>,
+    drag_moved: bool,
+    band_from: Option<Vector>,
+    dirty: bool,
+    // Why the last save failed, shown in the status bar until a save works
+    save_error: Option<String>,
+    preview: Game,
+}
+
+impl Editor {
+    pub fn new(level_name: &str) -> Result<Self, AssetError> {
+        let path: PathBuf = level_path(level_name)?;
+        let mut editor: Editor = Editor {
+            entries: read_lines(&path)?
+                .iter()
+                .map(|line| Entry::parse(line))
+                .collect(),
+            selected: Vec::new(),
+            clipboard: Vec::new(),
+            history: Vec::new(),
+            future: Vec::new(),
+            mode: Mode::Build,
+            template: 0,
+            camera: Camera::new(),
+            mouse: Vector::default(),
+            ctrl: false,
+            shift: false,
+            drag: None,
+            drag_moved: false,
+            band_from: None,
+            dirty: false,
+            save_error: None,
+            preview: Game::new(),
+            path,
+        };
+
+        editor.refresh();
+        Ok(editor)
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| entry.line()).collect()
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        let mut text: String = self.lines().join("\n");
+        text.push('\n');
+
+        fs::write(&self.path, text)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    // Rebuilds the preview, which draws the level exactly like the game would
+
+    fn refresh(&mut self) {
+        self.preview.load_level(&self.lines());
+    }
+
+    // Call before every change so it can be undone
+
+    fn checkpoint(&mut self) {
+        self.history.push(self.entries.clone());
+        self.future.clear();
+        self.dirty = true;
+    }
+
+    fn undo(&mut self) {
+        if let Some(entries) = self.history.pop() {
+            self.future
+                .push(std::mem::replace(&mut self.entries, entries));
+            self.selected.clear();
+            self.dirty = true;
+            self.refresh();
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(entries) = self.future.pop() {
+            self.history
+                .push(std::mem::replace(&mut self.entries, entries));
+            self.selected.clear();
+            self.dirty = true;
+            self.refresh();
+        }
+    }
+
+    fn band(&self) -> Rect {
+        let view: [f32; 4] = self.camera.view();
+
+        Rect::new(
+            Vector::new(view[0], view[1] + 10.0),
+            Vector::new(view[2] - view[0], MARKER_SIZE),
+        )
+    }
+
+    fn mouse_world(&self) -> Vector {
+        self.camera.screen_to_world(self.mouse)
+    }
+
+    // Topmost entry under `point`, later lines are drawn on top
+
+    fn entry_at(&self, point: Vector) -> Option<usize> {
+        let band: Rect = self.band();
+
+        (0..self.entries.len()).rev().find(|&i| {
+            self.entries[i]
+                .bounds(&band)
+                .is_some_and(|bounds| bounds.contains(point))
+        })
+    }
+
+    fn place(&mut self, cell: Vector) {
+        self.checkpoint();
+        self.entries
+            .push(Entry::parse(&template_line(self.template, cell)));
+        self.selected = vec![self.entries.len() - 1];
+        self.refresh();
+    }
+
+    fn delete_selected(&mut self) {
+        if self.selected.is_empty() {
+            return;
+        }
+
+        self.checkpoint();
+
+        for &i in self.selected.iter().rev() {
+            self.entries.remove(i);
+        }
+
+        self.selected.clear();
+        self.refresh();
+    }
+
+    fn move_selected(&mut self, delta: Vector) {
+        if self.selected.is_empty() {
+            return;
+        }
+
+        self.checkpoint();
+
+        for &i in self.selected.iter() {
+            self.entries[i].translate(delta);
+        }
+
+        self.refresh();
+    }
+
+    fn copy(&mut self) {
+        self.clipboard = self
+            .selected
+            .iter()
+            .map(|&i| self.entries[i].clone())
+            .collect();
+    }
+
+    // Pastes so the first copied object lands in the grid cell under the mouse
+
+    fn paste(&mut self) {
+        let Some(first) = self.clipboard.first() else {
+            return;
+        };
+
+        let delta: Vector = cell(self.mouse_world()) - cell(first.anchor());
+        let pasted: Vec<Entry> = self
+            .clipboard
+            .iter()
+            .map(|entry| {
+                let mut entry: Entry = entry.clone();
+                entry.translate(delta);
+                entry
+            })
+            .collect();
+
+        self.checkpoint();
+        self.selected = (self.entries.len()..self.entries.len() + pasted.len()).collect();
+        self.entries.extend(pasted);
+        self.refresh();
+    }
+
+    pub fn mouse_move(&mut self, pos: [f64; 2]) {
+        self.mouse = Vector::new(pos[0] as f32, pos[1] as f32);
+
+        // Dragging moves the selection a whole grid cell at a time
+        if let Some(from) = self.drag {
+            let delta: Vector = cell(self.mouse_world()) - from;
+
+            if delta != Vector::default() {
+                if !self.drag_moved {
+                    self.checkpoint();
+                    self.drag_moved = true;
+                }
+
+                for &i in self.selected.iter() {
+                    self.entries[i].translate(delta);
+                }
+
+                self.drag = Some(from + delta);
+                self.refresh();
+            }
+        }
+    }
+
+    // The wheel scrolls horizontally, or vertically while shift is held
+
+    pub fn scroll(&mut self, amount: [f64; 2]) {
+        if self.shift {
+            self.camera.center.y -= amount[1] as f32 * EDITOR_GRID;
+        } else {
+            self.camera.center.x -= amount[1] as f32 * EDITOR_GRID * 2.0;
+        }
+
+        self.camera.center.x += amount[0] as f32 * EDITOR_GRID * 2.0;
+    }
+
+    pub fn button(&mut self, button: Button, state: ButtonState) {
+        let pressed: bool = state == ButtonState::Press;
+
+        match button {
+            Button::Keyboard(Key::LCtrl | Key::RCtrl) => self.ctrl = pressed,
+            Button::Keyboard(Key::LShift | Key::RShift) => self.shift = pressed,
+            Button::Mouse(MouseButton::Left) if pressed => self.press(),
+            Button::Mouse(MouseButton::Left) => self.release(),
+            Button::Keyboard(key) if pressed => self.key(key),
+            _ => {}
+        }
+    }
+
+    fn press(&mut self) {
+        let point: Vector = self.mouse_world();
+
+        match (self.mode, self.entry_at(point)) {
+            (Mode::Build, None) => self.place(cell(point)),
+            (_, Some(i)) => {
+                if self.shift {
+                    match self.selected.binary_search(&i) {
+                        Ok(at) => {
+                            self.selected.remove(at);
+                        }
+                        Err(at) => self.selected.insert(at, i),
+                    }
+                } else if self.selected.binary_search(&i).is_err() {
+                    self.selected = vec![i];
+                }
+
+                self.drag = Some(cell(point));
+                self.drag_moved = false;
+            }
+            (Mode::Edit, None) => {
+                if !self.shift {
+                    self.selected.clear();
+                }
+
+                self.band_from = Some(point);
+            }
+        }
+    }
+
+    fn release(&mut self) {
+        self.drag = None;
+
+        // Box select adds everything the box touches
+        if let Some(from) = self.band_from.take() {
+            let area: Rect = Rect::around([from, self.mouse_world()]);
+            let band: Rect = self.band();
+
+            for (i, entry) in self.entries.iter().enumerate() {
+                if entry
+                    .bounds(&band)
+                    .is_some_and(|bounds| bounds.intersects(&area))
+                {
+                    self.selected.push(i);
+                }
+            }
+
+            self.selected.sort_unstable();
+            self.selected.dedup();
+        }
+    }
+
+    fn key(&mut self, key: Key) {
+        let g: f32 = EDITOR_GRID;
+
+        match key {
+            Key::S if self.ctrl => self.save_error = self.save().err().map(|x| x.to_string()),
+            Key::Z if self.ctrl && self.shift => self.redo(),
+            Key::Z if self.ctrl => self.undo(),
+            Key::Y if self.ctrl => self.redo(),
+            Key::C if self.ctrl => self.copy(),
+            Key::V if self.ctrl => self.paste(),
+            Key::A if self.ctrl => {
+                self.selected = (0..self.entries.len())
+                    .filter(|&i| self.entries[i].kind() != 0)
+                    .collect();
+            }
+            Key::Tab => {
+                self.mode = match self.mode {
+                    Mode::Build => Mode::Edit,
+                    Mode::Edit => Mode::Build,
+                };
+            }
+            Key::D1 => self.template = 0,
+            Key::D2 => self.template = 1,
+            Key::D3 => self.template = 2,
+            Key::D4 => self.template = 3,
+            Key::D5 => self.template = 4,
+            Key::D6 => self.template = 5,
+            Key::D7 => self.template = 6,
+            Key::Delete | Key::Backspace => self.delete_selected(),
+            Key::Left => self.move_selected(Vector::new(-g, 0.0)),
+            Key::Right => self.move_selected(Vector::new(g, 0.0)),
+            Key::Up => self.move_selected(Vector::new(0.0, -g)),
+            Key::Down => self.move_selected(Vector::new(0.0, g)),
+            Key::A => self.camera.center.x -= g * 4.0,
+            Key::D => self.camera.center.x += g * 4.0,
+            Key::W => self.camera.center.y -= g * 4.0,
+            Key::S => self.camera.center.y += g * 4.0,
+            _ => {}
+        }
+    }
+
+    pub fn render(&self, gl: &mut GlGraphics, font: &mut Text, viewport: Viewport) {
+        use graphics::*;
+
+        let view: [f32; 4] = self.camera.view();
+        let band: Rect = self.band();
+        let mouse: Vector = self.mouse_world();
+        let status: String = format!(
+            "{} mode | Tab switch, 1-7 object, Ctrl+S save, Esc play ",
+            match self.mode {
+                Mode::Build => "Build",
+                Mode::Edit => "Edit",
+            }
+        );
+        let selection: String = format!(
+            "{} selected{} ",
+            self.selected.len(),
+            match (&self.save_error, self.dirty) {
+                (Some(error), _) => format!(" | save failed: {}", error),
+                (None, true) => " | unsaved".to_string(),
+                (None, false) => String::new(),
+            }
+        );
+        let status_width: f64 = font.width(&status, 28) as f64;
+
+        gl.draw(viewport, |c: Context, gl: &mut GlGraphics| {
+            rectangle(
+                self.preview.colors.get(CHANNEL_BG),
+                [0.0, 0.0, WIDTH as f64, HEIGHT as f64],
+                c.transform,
+                gl,
+            );
+
+            let transform: [[f64; 3]; 2] = self.camera.transform(c.transform);
+            let grid_color: [f32; 4] = [1.0, 1.0, 1.0, 0.08];
+            let first: Vector = cell(Vector::new(view[0], view[1]));
+
+            let mut x: f32 = first.x;
+            while x <= view[2] {
+                line(
+                    grid_color,
+                    0.5,
+                    [x as f64, view[1] as f64, x as f64, view[3] as f64],
+                    transform,
+                    gl,
+                );
+                x += EDITOR_GRID;
+            }
+
+            let mut y: f32 = first.y;
+            while y <= view[3] {
+                line(
+                    grid_color,
+                    0.5,
+                    [view[0] as f64, y as f64, view[2] as f64, y as f64],
+                    transform,
+                    gl,
+                );
+                y += EDITOR_GRID;
+            }
+
+            self.preview.render_world(view, transform, gl);
+
+            for (i, entry) in self.entries.iter().enumerate() {
+                let Some(bounds) = entry.bounds(&band) else {
+                    continue;
+                };
+
+                if bounds.max().x < view[0] || bounds.pos.x > view[2] {
+                    continue;
+                }
+
+                if entry.is_marker() {
+                    let x: f64 = entry.number(1) as f64;
+
+                    line(
+                        [1.0, 0.6, 0.0, 0.3],
+                        1.0,
+                        [x, band.max().y as f64, x, view[3] as f64],
+                        transform,
+                        gl,
+                    );
+                    rectangle([1.0, 0.6, 0.0, 1.0], bounds.to_rectangle(), transform, gl);
+                } else if entry.kind() == 8 {
+                    // Collision blocks and start positions are invisible in game
+                    Rectangle::new_border([0.3, 0.5, 1.0, 1.0], 1.5).draw(
+                        bounds.to_rectangle(),
+                        &c.draw_state,
+                        transform,
+                        gl,
+                    );
+                } else if entry.kind() == 17 {
+                    Rectangle::new_border([0.0, 1.0, 1.0, 1.0], 1.5).draw(
+                        bounds.to_rectangle(),
+                        &c.draw_state,
+                        transform,
+                        gl,
+                    );
+                }
+
+                if self.selected.binary_search(&i).is_ok() {
+                    Rectangle::new_border([0.0, 1.0, 0.0, 1.0], 2.0).draw(
+                        bounds.to_rectangle(),
+                        &c.draw_state,
+                        transform,
+                        gl,
+                    );
+                }
+            }
+
+            // Outline of the object a click would place
+            if self.mode == Mode::Build && self.drag.is_none() {
+                let ghost: Entry = Entry::parse(&template_line(self.template, cell(mouse)));
+
+                if let Some(bounds) = ghost.bounds(&band) {
+                    Rectangle::new_border([1.0, 1.0, 1.0, 0.5], 1.5).draw(
+                        bounds.to_rectangle(),
+                        &c.draw_state,
+                        transform,
+                        gl,
+                    );
+                    font.render(
+                        [1.0, 1.0, 1.0, 0.8],
+                        TEMPLATE_NAMES[self.template],
+                        20,
+                        Align::Center,
+                        Context {
+                            transform: transform
+                                .trans(bounds.center().x as f64, bounds.pos.y as f64 - 8.0),
+                            ..c
+                        },
+                        gl,
+                    );
+                }
+            }
+
+            if let Some(from) = self.band_from {
+                Rectangle::new_border([1.0, 1.0, 1.0, 0.8], 1.0).draw(
+                    Rect::around([from, mouse]).to_rectangle(),
+                    &c.draw_state,
+                    transform,
+                    gl,
+                );
+            }
+
+            rectangle(
+                [0.0, 0.0, 0.0, 0.6],
+                [24.0, HEIGHT as f64 - 72.0, status_width + 24.0, 48.0],
+                c.transform,
+                gl,
+            );
+            font.render(
+                [1.0, 1.0, 1.0, 1.0],
+                status.as_str(),
+                28,
+                Align::Left,
+                c.trans(36.0, HEIGHT as f64 - 36.0),
+                gl,
+            );
+            font.render(
+                [1.0, 1.0, 1.0, 1.0],
+                selection.as_str(),
+                28,
+                Align::Right,
+                c.trans(WIDTH as f64 - 36.0, HEIGHT as f64 - 36.0),
+                gl,
+            );
+        });
+    }
+}
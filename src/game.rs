@@ -1,46 +1,125 @@
+use crate::assets::{AssetError, Assets};
 use crate::block::Block;
+use crate::camera::Camera;
+use crate::collider::Collider;
+use crate::collision_block::CollisionBlock;
+use crate::color::{lerp_color, ColorChannels, CHANNEL_BG, CHANNEL_GROUND, CHANNEL_PLAYER};
+use crate::config::Config;
 use crate::constants::{
-    BG_COLOR, BLOCK_COLOR, GROUND_COLOR, GROUND_COLOR_TRANSPARENT, GROUND_Y_FLIP, GROUND_Y_NORMAL,
-    HEIGHT, ORB_COLOR, PLAYER_COLOR, SPIKE_COLOR, WIDTH,
+    CHECKPOINT_INTERVAL, DEATH_EFFECT_TIME, DEATH_PIECES, FALL_MARGIN, HEIGHT, INDEX_CELL_WIDTH,
+    LEVELS_DIR, PLAYER_SCREEN_X, UPS, WIDTH,
 };
+use crate::crash_report::{Contact, CrashReport};
+use crate::easing::Easing;
+use crate::game_object::{GameObject, Interaction};
+use crate::input::{self, Bindings};
 use crate::orb::Orb;
+use crate::physics::Physics;
 use crate::player::Player;
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::section::{section_at, Section};
+use crate::settings::Settings;
+use crate::side::Side;
+use crate::spatial_index::SpatialIndex;
 use crate::spike::Spike;
+use crate::start_position::StartPosition;
 use crate::surface_result::SurfaceResult;
+use crate::text::{Align, Text};
+use crate::trigger::{Action, Trigger};
+use crate::util::{field_property, parse_nums};
 use crate::vector::Vector;
-use find_folder::Search;
-use freetype::{face::LoadFlag, Bitmap, Face, GlyphSlot, Library};
-use graphics::Context;
-use opengl_graphics::{GlGraphics, OpenGL, Texture, TextureSettings};
-use piston::input::{RenderArgs, UpdateArgs};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use graphics::{Context, Viewport};
+use opengl_graphics::GlGraphics;
+use piston::input::UpdateArgs;
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 pub struct Game {
-    pub gl: GlGraphics,
     pub frozen: bool,
     pub attempt_count: u32,
+    pub level_name: String,
     pub victory: bool,
     pub player: Player,
-    pub blocks: Vec<Block>,
-    pub spikes: Vec<Spike>,
-    pub orbs: Vec<Orb>,
+    pub objects: Vec<Box<dyn GameObject>>,
+    pub index: SpatialIndex,
+    pub groups: HashMap<u32, Vec<usize>>,
+    pub collision_ids: HashMap<u32, Vec<usize>>,
+    pub triggers: Vec<Trigger>,
+    pub actions: Vec<Action>,
+    pub colors: ColorChannels,
+    pub camera: Camera,
+    pub sections: Vec<Section>,
+    pub level_end: f32,
+    // Highest and lowest point of the level, falling past them in an open section kills
+    pub top: f32,
+    pub bottom: f32,
+    pub tick: u64,
+    pub crash_report: Option<CrashReport>,
+    pub physics: Physics,
+    // Physics file, read when the game is created rather than on every load
+    physics_config: Option<Config>,
+    // Level as loaded, so practice mode can rebuild it
+    pub lines: Vec<String>,
+    pub practice: bool,
+    // Tick and player position of every practice checkpoint
+    pub checkpoints: Vec<(u64, Vector)>,
+    // Earliest tick the next automatic checkpoint can be placed at
+    next_checkpoint: u64,
+    // Jump button changes and gravity flips by the tick they happened before
+    pub inputs: Vec<(u64, bool)>,
+    pub flips: Vec<u64>,
+    // Totals over every attempt since the level was opened
+    pub jumps: u32,
+    pub play_time: f32,
+    pub attempt_time: f32,
+    // Seconds since the player crashed, drives the death effect and auto retry
+    pub death_time: f32,
+    pub new_best: bool,
+    // Level came from the editor rather than its file, so results aren't recorded
+    pub playtest: bool,
+    // Attempt began at a start position, so it can't set a best or complete the level
+    pub from_start_position: bool,
 }
 
 impl Default for Game {
     fn default() -> Self {
-        let opengl: OpenGL = OpenGL::V4_5;
-
         Game {
-            gl: GlGraphics::new(opengl),
-            player: Player::new(),
+            player: Player::new(&Physics::new()),
             frozen: true,
             attempt_count: 0,
+            level_name: String::new(),
             victory: false,
-            blocks: Vec::new(),
-            spikes: Vec::new(),
-            orbs: Vec::new(),
+            objects: Vec::new(),
+            index: SpatialIndex::new(INDEX_CELL_WIDTH),
+            groups: HashMap::new(),
+            collision_ids: HashMap::new(),
+            triggers: Vec::new(),
+            actions: Vec::new(),
+            colors: ColorChannels::new(),
+            camera: Camera::new(),
+            sections: vec![Section::default()],
+            level_end: 0.0,
+            top: 0.0,
+            bottom: 0.0,
+            tick: 0,
+            crash_report: None,
+            physics: Physics::new(),
+            physics_config: None,
+            lines: Vec::new(),
+            practice: false,
+            checkpoints: Vec::new(),
+            next_checkpoint: 0,
+            inputs: Vec::new(),
+            flips: Vec::new(),
+            jumps: 0,
+            play_time: 0.0,
+            attempt_time: 0.0,
+            death_time: 0.0,
+            new_best: false,
+            playtest: false,
+            from_start_position: false,
         }
     }
 }
@@ -48,494 +127,1357 @@ impl Default for Game {
 impl Game {
     pub fn new() -> Self {
         Game {
+            physics_config: Physics::config(),
             ..Default::default()
         }
     }
 
-    pub fn render(&mut self, args: &RenderArgs, fps: i32) {
+    pub fn render(
+        &mut self,
+        gl: &mut GlGraphics,
+        font: &mut Text,
+        viewport: Viewport,
+        fps: i32,
+        settings: &Settings,
+    ) {
         use graphics::*;
 
+        let view: [f32; 4] = self.camera.view();
         let player_square: [f64; 4] = rectangle::square(0.0, 0.0, self.player.size as f64);
-        let ground_rect_flip: [f64; 4] =
-            rectangle::rectangle_by_corners(0.0, 0.0, WIDTH as f64, GROUND_Y_FLIP as f64);
-        let ground_rect: [f64; 4] = rectangle::rectangle_by_corners(
-            0.0,
-            GROUND_Y_NORMAL as f64,
-            WIDTH as f64,
-            HEIGHT as f64,
-        );
 
-        self.gl
-            .draw(args.viewport(), |c: Context, gl: &mut GlGraphics| {
-                clear(BG_COLOR, gl);
+        gl.draw(viewport, |c: Context, gl: &mut GlGraphics| {
+            rectangle(
+                self.colors.get(CHANNEL_BG),
+                [0.0, 0.0, WIDTH as f64, HEIGHT as f64],
+                c.transform,
+                gl,
+            );
 
-                let player_transform: [[f64; 3]; 2] = c
-                    .transform
-                    .trans(self.player.pos.x as f64, self.player.pos.y as f64)
-                    .rot_deg(if self.player.pos.x > self.player.jump.x {
-                        -self.player.angle as f64
-                    } else {
-                        self.player.angle as f64
-                    })
-                    .trans(
-                        -(self.player.size as f64) / 2.0,
-                        -(self.player.size as f64) / 2.0,
+            let world_transform: [[f64; 3]; 2] = self.camera.transform(c.transform);
+            let player_transform: [[f64; 3]; 2] = world_transform
+                .trans(self.player.pos.x as f64, self.player.pos.y as f64)
+                .rot_deg(if self.player.pos.x > self.player.jump.x {
+                    -self.player.angle as f64
+                } else {
+                    self.player.angle as f64
+                })
+                .trans(
+                    -(self.player.size as f64) / 2.0,
+                    -(self.player.size as f64) / 2.0,
+                );
+
+            if self.player.crashed {
+                self.render_death(world_transform, gl);
+            } else {
+                rectangle(
+                    self.colors.get(CHANNEL_PLAYER),
+                    player_square,
+                    player_transform,
+                    gl,
+                );
+            }
+
+            self.render_world(view, world_transform, gl);
+
+            if self.practice {
+                for &(_, pos) in self.checkpoints.iter() {
+                    polygon(
+                        [0.0, 1.0, 0.0, 0.8],
+                        &[[0.0, -20.0], [14.0, 0.0], [0.0, 20.0], [-14.0, 0.0]],
+                        world_transform.trans(pos.x as f64, pos.y as f64),
+                        gl,
+                    );
+                }
+            }
+
+            if settings.show_progress_bar {
+                let bar: [f64; 4] = [WIDTH as f64 / 2.0 - 300.0, 24.0, 600.0, 16.0];
+                let progress: f32 = self.progress();
+
+                rectangle([0.0, 0.0, 0.0, 0.5], bar, c.transform, gl);
+                rectangle(
+                    self.colors.get(CHANNEL_PLAYER),
+                    [bar[0], bar[1], bar[2] * progress as f64 / 100.0, bar[3]],
+                    c.transform,
+                    gl,
+                );
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!("{:.0}% ", progress).as_str(),
+                    28,
+                    Align::Left,
+                    c.trans(bar[0] + bar[2] + 16.0, 40.0),
+                    gl,
+                );
+            }
+
+            if self.victory {
+                self.render_results(font, &settings.bindings, c, gl);
+            } else if self.frozen && self.tick == 0 {
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!(
+                        "{} to jump ",
+                        settings.bindings.describe(input::Action::Jump)
+                    )
+                    .as_str(),
+                    36,
+                    Align::Left,
+                    c.trans(
+                        36.0,
+                        if self.player.gravity_flip {
+                            HEIGHT as f64 - 36.0
+                        } else {
+                            72.0
+                        },
+                    ),
+                    gl,
+                );
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    "Hold to keep jumping ",
+                    36,
+                    Align::Left,
+                    c.trans(
+                        36.0,
+                        if self.player.gravity_flip {
+                            HEIGHT as f64 - 72.0
+                        } else {
+                            112.0
+                        },
+                    ),
+                    gl,
+                );
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!(
+                        "{} to start / pause ",
+                        settings.bindings.describe(input::Action::Pause)
+                    )
+                    .as_str(),
+                    36,
+                    Align::Left,
+                    c.trans(
+                        36.0,
+                        if self.player.gravity_flip {
+                            HEIGHT as f64 - 108.0
+                        } else {
+                            152.0
+                        },
+                    ),
+                    gl,
+                );
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!(
+                        "{} to change gravity ",
+                        settings.bindings.describe(input::Action::FlipGravity)
+                    )
+                    .as_str(),
+                    36,
+                    Align::Left,
+                    c.trans(
+                        36.0,
+                        if self.player.gravity_flip {
+                            HEIGHT as f64 - 144.0
+                        } else {
+                            192.0
+                        },
+                    ),
+                    gl,
+                );
+            } else if self.player.crashed && !settings.auto_retry {
+                self.render_results(font, &settings.bindings, c, gl);
+
+                line(
+                    [1.0, 0.0, 0.0, 1.0],
+                    1.5,
+                    [
+                        self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
+                    ],
+                    world_transform,
+                    gl,
+                );
+                line(
+                    [1.0, 0.0, 0.0, 1.0],
+                    1.5,
+                    [
+                        self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
+                    ],
+                    world_transform,
+                    gl,
+                );
+                line(
+                    [1.0, 0.0, 0.0, 1.0],
+                    1.5,
+                    [
+                        self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
+                        self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
+                    ],
+                    world_transform,
+                    gl,
+                );
+                line(
+                    [1.0, 0.0, 0.0, 1.0],
+                    1.5,
+                    [
+                        self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
+                        self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
+                    ],
+                    world_transform,
+                    gl,
+                );
+            } else {
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!("Attempt {} ", self.attempt_count).as_str(),
+                    36,
+                    Align::Left,
+                    c.trans(36.0, 72.0),
+                    gl,
+                );
+
+                if self.practice {
+                    font.render(
+                        [0.0, 1.0, 0.0, 1.0],
+                        "Practice mode ",
+                        36,
+                        Align::Left,
+                        c.trans(36.0, 112.0),
+                        gl,
                     );
+                }
+            }
+
+            // Bottom right, so it never covers the instructions along the left edge
+            if settings.show_fps {
+                font.render(
+                    [1.0, 1.0, 1.0, 1.0],
+                    format!("FPS: {} ", fps).as_str(),
+                    36,
+                    Align::Right,
+                    c.trans(WIDTH as f64 - 36.0, HEIGHT as f64 - 36.0),
+                    gl,
+                );
+            }
+        });
+    }
 
-                rectangle(PLAYER_COLOR, player_square, player_transform, gl);
+    // The player bursts into pieces that fly apart and fade, with a ring spreading out behind them
+
+    fn render_death(&self, transform: [[f64; 3]; 2], gl: &mut GlGraphics) {
+        use graphics::*;
+
+        let t: f32 = (self.death_time / DEATH_EFFECT_TIME).min(1.0);
+        let spread: f64 = Easing::EaseOut.apply(t) as f64;
+        let size: f64 = self.player.size as f64;
+        let center: [[f64; 3]; 2] =
+            transform.trans(self.player.pos.x as f64, self.player.pos.y as f64);
+        let mut color: [f32; 4] = self.colors.get(CHANNEL_PLAYER);
+
+        color[3] *= 1.0 - t;
+
+        Ellipse::new_border(color, 3.0).draw(
+            rectangle::centered_square(0.0, 0.0, size * (0.5 + 1.5 * spread)),
+            &DrawState::default(),
+            center,
+            gl,
+        );
+
+        for i in 0..DEATH_PIECES {
+            let angle: f64 = i as f64 * std::f64::consts::TAU / DEATH_PIECES as f64;
+
+            rectangle(
+                color,
+                rectangle::centered_square(0.0, 0.0, size / 6.0),
+                center
+                    .rot_rad(angle)
+                    .trans(size * 2.0 * spread, 0.0)
+                    .rot_rad(spread * 4.0),
+                gl,
+            );
+        }
+    }
+
+    // End of attempt panel with the totals since the level was opened
+
+    fn render_results(
+        &self,
+        font: &mut Text,
+        bindings: &Bindings,
+        c: Context,
+        gl: &mut GlGraphics,
+    ) {
+        use graphics::*;
+
+        let center: f64 = WIDTH as f64 / 2.0;
+        let white: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+        let (title, title_color): (String, [f32; 4]) = if self.victory {
+            ("Level complete ".to_string(), [0.0, 1.0, 0.0, 1.0])
+        } else {
+            (
+                format!("Crashed at {:.0}% ", self.progress()),
+                [1.0, 0.0, 0.0, 1.0],
+            )
+        };
+        let hint: String = format!(
+            "{} {}, {} for the menu ",
+            bindings.describe(input::Action::Restart),
+            if self.victory {
+                "to play again"
+            } else if self.practice && !self.checkpoints.is_empty() {
+                "to go back to the checkpoint"
+            } else {
+                "to retry"
+            },
+            bindings.describe(input::Action::Pause)
+        );
+
+        rectangle(
+            [0.0, 0.0, 0.0, 0.7],
+            [center - 480.0, 200.0, 960.0, 560.0],
+            c.transform,
+            gl,
+        );
+        font.render(
+            title_color,
+            &title,
+            80,
+            Align::Center,
+            c.trans(center, 320.0),
+            gl,
+        );
+
+        if self.new_best {
+            font.render(
+                [1.0, 0.85, 0.0, 1.0],
+                format!("New best! {:.0}% ", self.progress()).as_str(),
+                40,
+                Align::Center,
+                c.trans(center, 390.0),
+                gl,
+            );
+        }
+
+        for (i, line) in [
+            format!("Attempts: {} ", self.attempt_count),
+            format!("Jumps: {} ", self.jumps()),
+            format!("Time: {} ", format_time(self.play_time)),
+        ]
+        .iter()
+        .enumerate()
+        {
+            font.render(
+                white,
+                line,
+                40,
+                Align::Center,
+                c.trans(center, 480.0 + i as f64 * 56.0),
+                gl,
+            );
+        }
+
+        if let (false, Some(report)) = (self.victory, &self.crash_report) {
+            font.render(
+                [1.0, 1.0, 1.0, 0.7],
+                format!("Hit {} ", report).as_str(),
+                28,
+                Align::Center,
+                c.trans(center, 680.0),
+                gl,
+            );
+        }
+
+        font.render(
+            [1.0, 1.0, 1.0, 0.7],
+            &hint,
+            28,
+            Align::Center,
+            c.trans(center, 730.0),
+            gl,
+        );
+    }
+
+    // Ground bands and every visible object inside `view`, without the player or any text
+
+    pub fn render_world(&self, view: [f32; 4], transform: [[f64; 3]; 2], gl: &mut GlGraphics) {
+        use graphics::*;
 
-                let ground_transform: [[f64; 3]; 2] = c.transform.trans(0.0, 0.0);
+        let ground_color: [f32; 4] = self.colors.get(CHANNEL_GROUND);
+        let ground_color_transparent: [f32; 4] = [
+            ground_color[0],
+            ground_color[1],
+            ground_color[2],
+            ground_color[3] * 0.5,
+        ];
 
+        for (i, section) in self.sections.iter().enumerate() {
+            let left: f32 = section.x.max(view[0]);
+            let right: f32 = self
+                .sections
+                .get(i + 1)
+                .map_or(view[2], |next| next.x.min(view[2]));
+
+            if left >= right {
+                continue;
+            }
+
+            if let Some(floor) = section.floor {
                 rectangle(
                     if self.player.gravity_flip {
-                        GROUND_COLOR_TRANSPARENT
+                        ground_color_transparent
                     } else {
-                        GROUND_COLOR
+                        ground_color
                     },
-                    ground_rect,
-                    ground_transform,
+                    rectangle::rectangle_by_corners(
+                        left as f64,
+                        floor as f64,
+                        right as f64,
+                        view[3].max(floor) as f64,
+                    ),
+                    transform,
                     gl,
                 );
+            }
+
+            if let Some(ceiling) = section.ceiling {
                 rectangle(
                     if self.player.gravity_flip {
-                        GROUND_COLOR
+                        ground_color
                     } else {
-                        GROUND_COLOR_TRANSPARENT
+                        ground_color_transparent
                     },
-                    ground_rect_flip,
-                    ground_transform,
+                    rectangle::rectangle_by_corners(
+                        left as f64,
+                        view[1].min(ceiling) as f64,
+                        right as f64,
+                        ceiling as f64,
+                    ),
+                    transform,
                     gl,
                 );
+            }
+        }
 
-                for block in self.blocks.iter() {
-                    let block_rect: [f64; 4] = rectangle::rectangle_by_corners(
-                        block.pos.x as f64,
-                        block.pos.y as f64,
-                        (block.pos.x + block.size.x) as f64,
-                        (block.pos.y + block.size.y) as f64,
-                    );
+        for object in self
+            .index
+            .query(view[0], view[2])
+            .into_iter()
+            .map(|i| &self.objects[i])
+            .filter(|object| object.props().visible())
+        {
+            object.render(&self.colors, transform, gl);
+        }
+    }
 
-                    rectangle(BLOCK_COLOR, block_rect, c.transform, gl);
-                }
+    // Returns the crash report on the tick the player dies
 
-                for spike in self.spikes.iter() {
-                    polygon(SPIKE_COLOR, &spike.vertices, c.transform, gl);
-                }
+    pub fn update(&mut self, args: &UpdateArgs) -> Option<&CrashReport> {
+        let crashed: bool = self.player.crashed;
 
-                for orb in self.orbs.iter() {
-                    ellipse(
-                        ORB_COLOR,
-                        [
-                            orb.pos.x as f64 - (orb.d as f64 / 2.0),
-                            orb.pos.y as f64 - (orb.d as f64 / 2.0),
-                            orb.d as f64,
-                            orb.d as f64,
-                        ],
-                        c.transform,
-                        gl,
-                    );
-                }
+        if !self.frozen && !crashed {
+            self.play_time += args.dt as f32;
+            self.attempt_time += args.dt as f32;
+        }
 
-                if self.frozen {
-                    if self.victory {
-                        render_text([0.0, 1.0, 0.0, 1.0], "Victory ", 64, 128, 96, gl, c);
-                    } else {
-                        render_text(
-                            [1.0, 1.0, 1.0, 1.0],
-                            "Left click to jump ",
-                            36,
-                            if self.player.gravity_flip {
-                                HEIGHT as i32 - 36
-                            } else {
-                                72
-                            },
-                            36,
-                            gl,
-                            c,
-                        );
-                        render_text(
-                            [1.0, 1.0, 1.0, 1.0],
-                            "Hold to keep jumping ",
-                            36,
-                            if self.player.gravity_flip {
-                                HEIGHT as i32 - 72
-                            } else {
-                                112
-                            },
-                            36,
-                            gl,
-                            c,
-                        );
-                        render_text(
-                            [1.0, 1.0, 1.0, 1.0],
-                            "ESC to freeze / unfreeze ",
-                            36,
-                            if self.player.gravity_flip {
-                                HEIGHT as i32 - 108
-                            } else {
-                                152
-                            },
-                            36,
-                            gl,
-                            c,
-                        );
-                        render_text(
-                            [1.0, 1.0, 1.0, 1.0],
-                            "Right click to change gravity ",
-                            36,
-                            if self.player.gravity_flip {
-                                HEIGHT as i32 - 144
-                            } else {
-                                192
-                            },
-                            36,
-                            gl,
-                            c,
-                        );
-                        render_text(
-                            [1.0, 1.0, 1.0, 1.0],
-                            "R to restart ",
-                            36,
-                            if self.player.gravity_flip {
-                                HEIGHT as i32 - 180
-                            } else {
-                                232
-                            },
-                            36,
-                            gl,
-                            c,
-                        );
-                    }
-                } else if self.player.crashed {
-                    render_text([1.0, 0.0, 0.0, 1.0], "Failure ", 64, 128, 96, gl, c);
+        if crashed {
+            self.death_time += args.dt as f32;
+        }
 
-                    line(
-                        [1.0, 0.0, 0.0, 1.0],
-                        1.5,
-                        [
-                            self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
-                        ],
-                        c.transform,
-                        gl,
-                    );
-                    line(
-                        [1.0, 0.0, 0.0, 1.0],
-                        1.5,
-                        [
-                            self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
-                        ],
-                        c.transform,
-                        gl,
-                    );
-                    line(
-                        [1.0, 0.0, 0.0, 1.0],
-                        1.5,
-                        [
-                            self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
-                            self.player.pos.x as f64 + (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
-                        ],
-                        c.transform,
-                        gl,
-                    );
-                    line(
-                        [1.0, 0.0, 0.0, 1.0],
-                        1.5,
-                        [
-                            self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.x as f64 - (self.player.size as f64 / 2.0),
-                            self.player.pos.y as f64 + (self.player.size as f64 / 2.0),
-                        ],
-                        c.transform,
-                        gl,
-                    );
-                } else {
-                    render_text(
-                        [1.0, 1.0, 1.0, 1.0],
-                        format!("Attempt {} ", self.attempt_count).as_str(),
-                        36,
-                        72,
-                        36,
-                        gl,
-                        c,
-                    );
-                }
+        self.step(args.dt as f32);
 
-                render_text(
-                    [1.0, 1.0, 1.0, 1.0],
-                    format!("FPS: {} ", fps).as_str(),
-                    36,
-                    HEIGHT as i32 - 36,
-                    36,
-                    gl,
-                    c,
-                );
-            });
+        if !crashed && self.player.crashed {
+            self.crash_report.as_ref()
+        } else {
+            None
+        }
     }
 
-    pub fn update(&mut self, args: &UpdateArgs) {
+    fn step(&mut self, dt: f32) {
         if !self.player.crashed && !self.frozen {
-            self.player.vel.y += self.player.acc.y;
-            self.player.pos.y += self.player.vel.y * args.dt as f32;
-            self.player.jump.x -= self.player.vel.x * args.dt as f32;
+            if self.inputs.last().is_some_and(|&(_, jumping)| jumping) != self.player.jumping {
+                self.inputs.push((self.tick, self.player.jumping));
+            }
 
-            if !self.player.grounded {
-                let d: f32 = self.player.pos.dist(&self.player.jump);
+            self.tick += 1;
 
-                let angle: f32 = if self.player.gravity_flip {
-                    -((self.player.pos.y - self.player.jump.y) / d)
-                        .asin()
-                        .to_degrees()
-                } else {
-                    ((self.player.jump.y - self.player.pos.y) / d)
-                        .asin()
-                        .to_degrees()
-                };
+            let prev_x: f32 = self.player.pos.x;
+            self.player.pos.x += self.player.vel.x * dt;
+
+            self.update_triggers(dt);
 
-                self.player.angle = angle;
+            // Everything below is swept from here so fast movement can't skip over thin objects
+            let prev: Vector = Vector::new(prev_x, self.player.pos.y);
+
+            // Gravity is tuned per tick at UPS, scale it so other update rates follow the same arc
+            self.player.vel.y += self.player.acc.y * dt * UPS as f32;
+            self.player.pos.y += self.player.vel.y * dt;
+
+            if !self.player.grounded {
+                // Tilt towards the point the jump arc is heading for, the same for both gravities
+                let heading: Vector = (self.player.jump - self.player.pos).normalize();
+
+                self.player.angle = heading.y.asin().to_degrees();
             }
 
-            let ground_check: SurfaceResult = self.player.on_ground();
+            let ground_check: SurfaceResult = self
+                .player
+                .on_ground(section_at(&self.sections, self.player.pos.x));
 
             match ground_check {
-                SurfaceResult::OnSurface(y) => {
-                    self.player.angle = 0.0;
-                    self.player.grounded = true;
-                    self.player.jump = Vector::new(f32::MAX, f32::MAX);
-                    self.player.pos.y = y;
-                    self.player.vel.y = 0.0;
-                }
+                SurfaceResult::OnSurface(y) => self.player.land(y),
                 SurfaceResult::NotOnSurface => {}
             }
 
-            let mut no_blocks: bool = self.blocks.is_empty();
-            let mut no_spikes: bool = self.spikes.is_empty();
-            let mut no_orbs: bool = self.orbs.is_empty();
+            let samples: Vec<Vector> = self.player.sweep_samples(prev);
+            let end: Vector = self.player.pos;
 
-            let mut k: usize = 0;
-            while k < self.orbs.len() {
-                let orb: &mut Orb = &mut self.orbs[k];
-                orb.pos.x -= self.player.vel.x * args.dt as f32;
+            // Only objects in the grid columns around the swept path can touch the player
+            let half: f32 = self.player.size as f32 / 2.0;
+            let nearby: Vec<usize> = self
+                .index
+                .query(prev.x.min(end.x) - half, prev.x.max(end.x) + half);
 
-                if orb.pos.x + (orb.d / 2.0) < 0.0 {
-                    self.orbs.remove(k);
+            for &i in nearby.iter() {
+                let object: &mut Box<dyn GameObject> = &mut self.objects[i];
 
-                    if self.orbs.is_empty() {
-                        no_orbs = true;
-                        break;
-                    } else {
-                        continue;
-                    }
-                } else if self.player.check_orb_collide(orb)
-                    && self.player.jumping
-                    && !orb.activated
-                {
-                    orb.activated = true;
-                    self.player.jump = Vector::new(self.player.pos.x + 250.0, self.player.pos.y);
-                    self.player.grounded = false;
-                    self.player.vel.y = if self.player.gravity_flip {
-                        self.player.acc.y.powi(2)
-                    } else {
-                        -self.player.acc.y.powi(2)
-                    };
+                if !object.props().solid() || object.interaction() != Interaction::Touch {
+                    continue;
                 }
 
-                k += 1;
+                let collider: Collider = object.collider();
+                let touched: bool = samples.iter().any(|&sample| {
+                    self.player.pos = sample;
+                    collider.intersects_rect(&self.player.hitbox())
+                });
+                self.player.pos = end;
+
+                if touched {
+                    object.on_touch(&mut self.player);
+                }
+            }
+
+            // Hazards kill on any contact with the full box. Solids that can't be swept as
+            // rectangles kill once the solid box touches them
+            for &sample in samples.iter() {
+                self.player.pos = sample;
+
+                let hitbox: Rect = self.player.hitbox();
+                let solid_hitbox: Rect = self.player.solid_hitbox();
+                let hit: Option<&Box<dyn GameObject>> = nearby
+                    .iter()
+                    .map(|&i| &self.objects[i])
+                    .filter(|object| object.props().solid())
+                    .find(|object| match (object.interaction(), object.collider()) {
+                        (Interaction::Hazard, collider) => collider.intersects_rect(&hitbox),
+                        (Interaction::Solid, Collider::Rect(_)) => false,
+                        (Interaction::Solid, collider) => collider.intersects_rect(&solid_hitbox),
+                        _ => false,
+                    });
+
+                if let Some(object) = hit {
+                    self.player.crashed = true;
+                    self.crash_report = Some(CrashReport::new(
+                        object.name(),
+                        object.props().line,
+                        object.contact(&self.player),
+                        self.tick,
+                        self.player.pos,
+                    ));
+                    return;
+                }
             }
 
-            let mut j: usize = 0;
-            while j < self.spikes.len() {
-                let spike: &mut Spike = &mut self.spikes[j];
-                spike.pos.x -= self.player.vel.x * args.dt as f32;
-                spike.vertices[0][0] = (spike.pos.x - (spike.size.x / 2.0)) as f64;
-                spike.vertices[1][0] = (spike.pos.x + (spike.size.x / 2.0)) as f64;
-                spike.vertices[2][0] = spike.pos.x as f64;
+            self.player.pos = end;
 
-                if spike.pos.x + (spike.size.x / 2.0) < 0.0 {
-                    self.spikes.remove(j);
+            // The full box sweeps for surfaces to land on, the solid box for deaths, and whichever
+            // happens first along the path wins
+            let solid_half: f32 = self.player.solid_half();
+            let mut landing: Option<(f32, f32)> = None;
+            let mut death: Option<(f32, usize, Side)> = None;
 
-                    if self.spikes.is_empty() {
-                        no_spikes = true;
-                        break;
+            for (i, object) in
+                nearby
+                    .iter()
+                    .map(|&i| (i, &self.objects[i]))
+                    .filter(|(_, object)| {
+                        object.props().solid() && object.interaction() == Interaction::Solid
+                    })
+            {
+                let Collider::Rect(rect) = object.collider() else {
+                    continue;
+                };
+
+                if let Some((t, side)) = self.player.sweep_rect(prev, half, &rect) {
+                    let lands: bool = match side {
+                        Side::Top => !self.player.gravity_flip,
+                        Side::Bottom => self.player.gravity_flip,
+                        Side::Left | Side::Right => false,
+                    };
+
+                    if lands && landing.is_none_or(|(first, _)| t < first) {
+                        landing = Some((
+                            t,
+                            if self.player.gravity_flip {
+                                rect.max().y + half
+                            } else {
+                                rect.pos.y - half
+                            },
+                        ));
                     }
-                } else {
-                    self.player.check_spike_crash(spike);
                 }
 
-                if self.player.crashed {
-                    return;
-                } else {
-                    j += 1;
+                if let Some((t, side)) = self.player.sweep_rect(prev, solid_half, &rect) {
+                    if death.is_none_or(|(first, _, _)| t < first) {
+                        death = Some((t, i, side));
+                    }
                 }
             }
 
-            let mut i: usize = 0;
-            while i < self.blocks.len() {
-                let block: &mut Block = &mut self.blocks[i];
-                block.pos.x -= self.player.vel.x * args.dt as f32;
+            match (landing, death) {
+                (landing, Some((t, i, side))) if landing.is_none_or(|(first, _)| t < first) => {
+                    self.player.pos = prev.lerp(self.player.pos, t);
+                    self.player.crashed = true;
+                    self.crash_report = Some(CrashReport::new(
+                        self.objects[i].name(),
+                        self.objects[i].props().line,
+                        Contact::Side(side),
+                        self.tick,
+                        self.player.pos,
+                    ));
+                    return;
+                }
+                (Some((_, y)), _) => self.player.land(y),
+                _ => {}
+            }
 
-                if block.pos.x + block.size.x < 0.0 {
-                    self.blocks.remove(i);
+            for object in nearby.iter().map(|&i| &self.objects[i]).filter(|object| {
+                object.props().solid() && object.interaction() == Interaction::Solid
+            }) {
+                let collider: Collider = object.collider();
 
-                    if self.blocks.is_empty() {
-                        no_blocks = true;
-                        break;
-                    }
-                } else {
-                    let surface_check: SurfaceResult = self.player.on_block(block);
-
-                    match surface_check {
-                        SurfaceResult::OnSurface(y) => {
-                            self.player.angle = 0.0;
-                            self.player.grounded = true;
-                            self.player.jump = Vector::new(f32::MAX, f32::MAX);
-                            self.player.vel.y = 0.0;
-                            self.player.pos.y = y;
+                if !collider.intersects_rect(&self.player.solid_hitbox()) {
+                    if let Collider::Rect(rect) = collider {
+                        match self.player.on_rect(&rect) {
+                            SurfaceResult::OnSurface(y) => self.player.land(y),
+                            SurfaceResult::NotOnSurface => {}
                         }
-                        SurfaceResult::NotOnSurface => {}
                     }
 
-                    self.player.check_block_crash(block);
+                    continue;
                 }
 
-                if self.player.crashed {
-                    return;
-                } else {
-                    i += 1;
-                }
+                self.player.crashed = true;
+                self.crash_report = Some(CrashReport::new(
+                    object.name(),
+                    object.props().line,
+                    object.contact(&self.player),
+                    self.tick,
+                    self.player.pos,
+                ));
+                return;
+            }
+
+            // Open sections have nothing to land on, so falling out of the level ends the run
+            let section: &Section = section_at(&self.sections, self.player.pos.x);
+            let fell: bool = if self.player.gravity_flip {
+                section.ceiling.is_none() && self.player.pos.y + half < self.top - FALL_MARGIN
+            } else {
+                section.floor.is_none() && self.player.pos.y - half > self.bottom + FALL_MARGIN
+            };
+
+            if fell {
+                self.player.crashed = true;
+                self.crash_report = Some(CrashReport::new(
+                    "section",
+                    section.line,
+                    Contact::OutOfBounds,
+                    self.tick,
+                    self.player.pos,
+                ));
+                return;
+            }
+
+            if self.practice && self.player.grounded && self.tick >= self.next_checkpoint {
+                self.place_checkpoint();
             }
 
             if self.player.grounded && self.player.jumping {
-                self.player.jump = Vector::new(self.player.pos.x + 250.0, self.player.pos.y);
-                self.player.grounded = false;
-                self.player.vel.y = if self.player.gravity_flip {
-                    self.player.acc.y.powi(2)
-                } else {
-                    -self.player.acc.y.powi(2)
-                };
+                self.player.launch();
             } else {
                 self.player.grounded = false;
             }
 
-            self.victory = no_blocks && no_spikes && no_orbs;
-            self.frozen = no_blocks && no_spikes && no_orbs;
+            self.camera.follow(&self.player);
+
+            // The level ends once the last object has scrolled off the left edge of the screen
+            self.victory = self.player.pos.x - PLAYER_SCREEN_X > self.level_end;
+            self.frozen = self.victory;
+        }
+    }
+
+    // How far into the level the player is, as a percentage
+
+    pub fn progress(&self) -> f32 {
+        if self.victory {
+            return 100.0;
+        }
+
+        ((self.player.pos.x - PLAYER_SCREEN_X) / self.level_end * 100.0).clamp(0.0, 100.0)
+    }
+
+    pub fn flip_gravity(&mut self) {
+        self.flips.push(self.tick);
+        self.player
+            .flip_gravity(section_at(&self.sections, self.player.pos.x));
+    }
+
+    pub fn jumps(&self) -> u32 {
+        self.jumps + self.player.jumps
+    }
+
+    pub fn set_practice(&mut self, practice: bool) {
+        self.practice = practice;
+        self.checkpoints.clear();
+    }
+
+    // Checkpoints can also be placed and removed by hand. Either one holds off the next
+    // automatic checkpoint, so a removed one isn't put straight back
+
+    pub fn place_checkpoint(&mut self) {
+        if self.practice && !self.player.crashed && !self.frozen {
+            self.checkpoints.push((self.tick, self.player.pos));
+            self.next_checkpoint = self.tick + CHECKPOINT_INTERVAL;
+        }
+    }
+
+    pub fn remove_checkpoint(&mut self) {
+        if self.practice {
+            self.checkpoints.pop();
+            self.next_checkpoint = self.tick + CHECKPOINT_INTERVAL;
+        }
+    }
+
+    // Rebuilds the level and replays the recorded input up to the last checkpoint, so moved
+    // objects, colors and the camera are exactly as they were when it was placed
+
+    pub fn respawn(&mut self) {
+        // Without a checkpoint nothing is replayed and this is a plain restart
+        let checkpoint: u64 = self.checkpoints.last().map_or(0, |&(tick, _)| tick);
+        let lines: Vec<String> = std::mem::take(&mut self.lines);
+        let checkpoints: Vec<(u64, Vector)> = std::mem::take(&mut self.checkpoints);
+        let mut inputs: Vec<(u64, bool)> = std::mem::take(&mut self.inputs);
+        let mut flips: Vec<u64> = std::mem::take(&mut self.flips);
+        let dt: f32 = 1.0 / UPS as f32;
+
+        inputs.retain(|&(tick, _)| tick < checkpoint);
+        flips.retain(|&tick| tick < checkpoint);
+
+        self.load_level(&lines);
+        self.frozen = false;
+
+        let mut next_input: usize = 0;
+
+        while self.tick < checkpoint && !self.player.crashed {
+            while next_input < inputs.len() && inputs[next_input].0 <= self.tick {
+                self.player.jumping = inputs[next_input].1;
+                next_input += 1;
+            }
+
+            for _ in flips.iter().filter(|&&tick| tick == self.tick) {
+                self.player
+                    .flip_gravity(section_at(&self.sections, self.player.pos.x));
+            }
+
+            self.step(dt);
+        }
+
+        // Those jumps were already counted by the attempt that placed the checkpoint
+        self.player.jumps = 0;
+        self.player.jumping = false;
+        self.checkpoints = checkpoints;
+        self.next_checkpoint = self.tick + CHECKPOINT_INTERVAL;
+        self.inputs = inputs;
+        self.flips = flips;
+    }
+
+    fn update_triggers(&mut self, dt: f32) {
+        for trigger in self.triggers.iter_mut() {
+            if !trigger.activated && !trigger.spawn_triggered && trigger.x <= self.player.pos.x {
+                trigger.activated = true;
+                self.actions
+                    .push(trigger.action(&self.colors, &self.camera));
+            }
+        }
+
+        let mut spawned: Vec<u32> = Vec::new();
+
+        let mut a: usize = 0;
+        while a < self.actions.len() {
+            let finished: bool = match &mut self.actions[a] {
+                Action::Move {
+                    group,
+                    offset,
+                    tween,
+                } => {
+                    let (before, after): (f32, f32) = tween.advance(dt);
+                    let delta: Vector = *offset * (after - before);
+                    let group: u32 = *group;
+                    let finished: bool = tween.finished();
+
+                    self.move_group(group, delta);
+                    finished
+                }
+                Action::Color {
+                    channel,
+                    from,
+                    to,
+                    tween,
+                } => {
+                    tween.advance(dt);
+                    self.colors
+                        .set(channel, lerp_color(*from, *to, tween.progress()));
+                    tween.finished()
+                }
+                Action::Toggle { group, enable } => {
+                    let (group, enable): (u32, bool) = (*group, *enable);
+
+                    self.toggle_group(group, enable);
+                    true
+                }
+                Action::Spawn { group, delay } => {
+                    *delay -= dt;
+
+                    if *delay <= 0.0 {
+                        spawned.push(*group);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Action::Collision {
+                    block_a,
+                    block_b,
+                    group,
+                    overlapping,
+                } => {
+                    let ids_a: &[usize] = self.collision_ids.get(block_a).map_or(&[], |x| x);
+                    let ids_b: &[usize] = self.collision_ids.get(block_b).map_or(&[], |x| x);
+                    let now_overlapping: bool = ids_a
+                        .iter()
+                        .filter(|&&a| self.objects[a].props().enabled)
+                        .any(|&a| {
+                            let bounds: Rect = self.objects[a].collider().bounds();
+
+                            ids_b
+                                .iter()
+                                .filter(|&&b| self.objects[b].props().enabled)
+                                .any(|&b| {
+                                    a != b && self.objects[b].collider().intersects_rect(&bounds)
+                                })
+                        });
+
+                    if now_overlapping && !*overlapping {
+                        spawned.push(*group);
+                    }
+
+                    *overlapping = now_overlapping;
+                    false
+                }
+                Action::Rotate {
+                    group,
+                    center,
+                    degrees,
+                    tween,
+                } => {
+                    let (before, after): (f32, f32) = tween.advance(dt);
+                    let (group, center, delta): (u32, u32, f32) =
+                        (*group, *center, *degrees * (after - before));
+                    let finished: bool = tween.finished();
+
+                    self.rotate_group(group, center, delta);
+                    finished
+                }
+                Action::Alpha {
+                    group,
+                    opacity,
+                    tween,
+                } => {
+                    let (before, after): (f32, f32) = tween.advance(dt);
+
+                    // Each object eases from its own starting alpha towards the target
+                    let fraction: f32 = if before >= 1.0 {
+                        1.0
+                    } else {
+                        (after - before) / (1.0 - before)
+                    };
+                    let (group, opacity): (u32, f32) = (*group, *opacity);
+                    let finished: bool = tween.finished();
+
+                    self.fade_group(group, opacity, fraction);
+                    finished
+                }
+                Action::CameraZoom { from, zoom, tween } => {
+                    tween.advance(dt);
+                    self.camera.zoom = *from + (*zoom - *from) * tween.progress();
+                    tween.finished()
+                }
+                Action::CameraOffset { offset, tween } => {
+                    let (before, after): (f32, f32) = tween.advance(dt);
+
+                    self.camera.offset += *offset * (after - before);
+                    tween.finished()
+                }
+                Action::CameraStatic {
+                    from,
+                    target,
+                    tween,
+                } => {
+                    tween.advance(dt);
+
+                    let goal: f32 = match target {
+                        Some(target) => {
+                            self.camera.lock = *target;
+                            1.0
+                        }
+                        None => 0.0,
+                    };
+
+                    self.camera.lock_blend = *from + (goal - *from) * tween.progress();
+                    tween.finished()
+                }
+            };
+
+            if finished {
+                self.actions.remove(a);
+            } else {
+                a += 1;
+            }
+        }
+
+        // Spawned triggers start next tick, so a trigger spawning its own group can't loop forever
+        for group in spawned {
+            self.spawn_group(group);
+        }
+    }
+
+    fn spawn_group(&mut self, group: u32) {
+        for trigger in self
+            .triggers
+            .iter_mut()
+            .filter(|trigger| trigger.group == group)
+        {
+            trigger.activated = true;
+            self.actions
+                .push(trigger.action(&self.colors, &self.camera));
         }
     }
 
-    pub fn initialize_level(&mut self, level_name: &str) {
-        self.player = Player::new();
-        self.blocks.clear();
-        self.spikes.clear();
-        self.orbs.clear();
+    fn toggle_group(&mut self, group: u32, enable: bool) {
+        for &i in self.groups.get(&group).into_iter().flatten() {
+            self.objects[i].props_mut().enabled = enable;
+        }
+    }
+
+    fn group_center(&self, group: u32) -> Option<Vector> {
+        self.groups
+            .get(&group)
+            .and_then(|ids| ids.first())
+            .map(|&i| self.objects[i].center())
+    }
+
+    // Without a center object every object spins in place around its own center
+
+    fn rotate_group(&mut self, group: u32, center: u32, degrees: f32) {
+        let pivot: Option<Vector> = if center == 0 {
+            None
+        } else {
+            self.group_center(center)
+        };
+
+        for &i in self.groups.get(&group).into_iter().flatten() {
+            self.objects[i].rotate(pivot, degrees);
+
+            let (min_x, max_x): (f32, f32) = self.objects[i].x_extent();
+            self.index.update(i, min_x, max_x);
+        }
+    }
+
+    fn fade_group(&mut self, group: u32, opacity: f32, fraction: f32) {
+        for &i in self.groups.get(&group).into_iter().flatten() {
+            let props: &mut Properties = self.objects[i].props_mut();
+            props.alpha += (opacity - props.alpha) * fraction;
+        }
+    }
+
+    fn move_group(&mut self, group: u32, delta: Vector) {
+        let mut carried: bool = false;
+
+        for &i in self.groups.get(&group).into_iter().flatten() {
+            let object: &mut Box<dyn GameObject> = &mut self.objects[i];
+
+            if let (false, true, Interaction::Solid, Collider::Rect(rect)) = (
+                carried,
+                object.props().solid(),
+                object.interaction(),
+                object.collider(),
+            ) {
+                if self.player.standing_on(&rect) {
+                    carried = true;
+                    self.player.pos.y += delta.y;
+                }
+            }
+
+            object.translate(delta);
+
+            let (min_x, max_x): (f32, f32) = object.x_extent();
+            self.index.update(i, min_x, max_x);
+        }
+    }
+
+    // Leaves the game as it was when the level can't be read
+
+    pub fn initialize_level(&mut self, level_name: &str) -> Result<(), AssetError> {
+        let lines: Vec<String> = read_level(level_name)?;
+
+        self.level_name = level_name.to_string();
+        self.playtest = false;
+        self.checkpoints.clear();
+        self.inputs.clear();
+        self.flips.clear();
+        self.load_level(&lines);
+        Ok(())
+    }
+
+    pub fn load_level(&mut self, lines: &[String]) {
+        self.lines = lines.to_vec();
+        self.jumps += self.player.jumps;
+        self.attempt_time = 0.0;
+        self.death_time = 0.0;
+        self.new_best = false;
+        self.physics = Physics::from_config(self.physics_config.as_ref(), "default");
+        self.objects.clear();
+        self.triggers.clear();
+        self.actions.clear();
+        self.colors = ColorChannels::new();
+        self.camera = Camera::new();
+        self.sections = vec![Section::default()];
+        self.tick = 0;
+        self.next_checkpoint = CHECKPOINT_INTERVAL;
+        self.crash_report = None;
         self.attempt_count += 1;
         self.frozen = true;
         self.victory = false;
 
-        let file_path: String = format!("levels/{}", level_name);
-        let path: &Path = Path::new(&file_path);
-        let file = File::open(path).expect("Failed to open level contents");
-        let reader: BufReader<File> = BufReader::new(file);
-        let mut lines: Vec<String> = Vec::new();
-
-        for line in reader.lines() {
-            let line = line.expect("Failed to read line");
-            lines.push(line);
-        }
+        let mut start: Option<StartPosition> = None;
 
-        for line in &lines {
+        for (i, line) in lines.iter().enumerate() {
             let fields_raw: Vec<&str> = line.split(',').collect();
-            let entry_type: f32 = fields_raw[0].parse::<f32>().unwrap();
+            let entry_type: u32 = fields_raw[0].parse::<u32>().unwrap();
+            let props: Properties = Properties::parse(&fields_raw, i + 1);
 
             match entry_type {
-                x if x == 1.0 => {
-                    let fields_nums: Vec<f32> = fields_raw[..=fields_raw.len() - 1]
-                        .iter()
-                        .map(|x| x.parse::<f32>().unwrap())
-                        .collect();
-                    self.blocks.push(Block::new(
-                        Vector::new(fields_nums[1], fields_nums[2]),
-                        Vector::new(fields_nums[3], fields_nums[4]),
-                    ));
-                }
-                x if x == 2.0 => {
-                    let fields_nums: Vec<f32> = fields_raw[..=fields_raw.len() - 2]
-                        .iter()
-                        .map(|x| x.parse::<f32>().unwrap())
-                        .collect();
-                    self.spikes.push(Spike::new(
-                        Vector::new(fields_nums[1], fields_nums[2]),
-                        Vector::new(fields_nums[3], fields_nums[4]),
-                        fields_raw[5].parse::<bool>().unwrap(),
-                    ))
+                0 if fields_raw[1] == "color" => {
+                    // 0,color,channel,r,g,b,a
+                    let fields_nums: Vec<f32> = parse_nums(&fields_raw[3..=6]);
+                    self.colors.set(
+                        fields_raw[2],
+                        [
+                            fields_nums[0],
+                            fields_nums[1],
+                            fields_nums[2],
+                            fields_nums[3],
+                        ],
+                    );
                 }
-                x if x == 3.0 => {
-                    let fields_nums: Vec<f32> = fields_raw[..=fields_raw.len() - 1]
-                        .iter()
-                        .map(|x| x.parse::<f32>().unwrap())
-                        .collect();
-                    self.orbs.push(Orb::new(
-                        Vector::new(fields_nums[1], fields_nums[2]),
-                        fields_nums[3],
-                    ));
+                0 if fields_raw[1] == "physics" => {
+                    // 0,physics,profile with optional key=value overrides after it
+                    self.physics =
+                        Physics::from_config(self.physics_config.as_ref(), fields_raw[2]);
+                    self.physics.apply(|key| field_property(&fields_raw, key));
                 }
+                1 => self
+                    .objects
+                    .push(Box::new(Block::parse(&fields_raw, props))),
+                2 => self
+                    .objects
+                    .push(Box::new(Spike::parse(&fields_raw, props))),
+                3 => self.objects.push(Box::new(Orb::parse(&fields_raw, props))),
+                8 => self
+                    .objects
+                    .push(Box::new(CollisionBlock::parse(&fields_raw, props))),
+                4..=15 => self.triggers.extend(Trigger::parse(&fields_raw, &props)),
+                16 => self.sections.push(Section::parse(&fields_raw, &props)),
+                // The last enabled start position wins
+                17 if props.enabled => start = Some(StartPosition::parse(&fields_raw)),
                 _ => {}
             }
         }
+
+        self.sections.sort_by(|a, b| a.x.total_cmp(&b.x));
+        self.from_start_position = start.is_some();
+        self.player = match &start {
+            Some(start) => {
+                // Triggers behind the start position never fire, like the level was begun there
+                for trigger in self
+                    .triggers
+                    .iter_mut()
+                    .filter(|trigger| trigger.x < start.pos.x)
+                {
+                    trigger.activated = true;
+                }
+
+                Player::at_start(&self.physics, start)
+            }
+            None => Player::new(&self.physics),
+        };
+
+        self.index.clear();
+        self.groups.clear();
+        self.collision_ids.clear();
+
+        for (i, object) in self.objects.iter().enumerate() {
+            let (min_x, max_x): (f32, f32) = object.x_extent();
+            self.index.insert(min_x, max_x);
+            self.groups.entry(object.props().group).or_default().push(i);
+
+            if let Some(id) = object.collision_id() {
+                self.collision_ids.entry(id).or_default().push(i);
+            }
+        }
+
+        self.level_end = self
+            .objects
+            .iter()
+            .filter(|object| object.interaction() != Interaction::Passive)
+            .map(|object| object.bounds().max().x)
+            .fold(f32::MIN, f32::max);
+
+        let heights: Vec<f32> = self
+            .objects
+            .iter()
+            .flat_map(|object| [object.bounds().pos.y, object.bounds().max().y])
+            .chain(
+                self.sections
+                    .iter()
+                    .flat_map(|section| [section.floor, section.ceiling])
+                    .flatten(),
+            )
+            .chain([self.player.pos.y])
+            .collect();
+        self.top = heights.iter().copied().fold(f32::MAX, f32::min);
+        self.bottom = heights.iter().copied().fold(f32::MIN, f32::max);
+
+        self.camera.follow(&self.player);
     }
 }
 
-fn glyphs(face: &mut Face, text: &str, mut x: i32, mut y: i32) -> Vec<(Texture, [f64; 2])> {
-    let mut res: Vec<(Texture, [f64; 2])> = vec![];
-    for ch in text.chars() {
-        face.load_char(ch as usize, LoadFlag::RENDER).unwrap();
-        let g: &GlyphSlot = face.glyph();
-
-        let bitmap: Bitmap = g.bitmap();
-        let texture: Texture = Texture::from_memory_alpha(
-            bitmap.buffer(),
-            bitmap.width() as u32,
-            bitmap.rows() as u32,
-            &TextureSettings::new(),
-        )
-        .unwrap();
-        res.push((
-            texture,
-            [(x + g.bitmap_left()) as f64, (y - g.bitmap_top()) as f64],
+// Minutes and seconds, like 2:05
+
+fn format_time(seconds: f32) -> String {
+    let seconds: u32 = seconds as u32;
+
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// Where `level_name` is in the levels folder of the assets
+
+pub fn level_path(level_name: &str) -> Result<PathBuf, AssetError> {
+    Assets::new().find(&format!("{}/{}", LEVELS_DIR, level_name))
+}
+
+pub fn read_level(level_name: &str) -> Result<Vec<String>, AssetError> {
+    read_lines(&level_path(level_name)?)
+}
+
+pub fn read_lines(path: &Path) -> Result<Vec<String>, AssetError> {
+    fs::read_to_string(path)
+        .map(|text| text.lines().map(|line| line.to_string()).collect())
+        .map_err(|error| AssetError::Load {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::GROUND_Y_NORMAL;
+
+    fn lines(level: &[&str]) -> Vec<String> {
+        level.iter().map(|x| x.to_string()).collect()
+    }
+
+    // Steps at `dt` until the run ends, returning the crash report if it ended in one
+
+    fn play(level: &[&str], dt: f64, flip: bool) -> Option<CrashReport> {
+        play_holding(level, dt, flip, false)
+    }
+
+    fn play_holding(level: &[&str], dt: f64, flip: bool, jump: bool) -> Option<CrashReport> {
+        let mut game: Game = Game::new();
+        game.load_level(&lines(level));
+        game.frozen = false;
+        game.player.jumping = jump;
+
+        if flip {
+            game.flip_gravity();
+        }
+
+        let args: UpdateArgs = UpdateArgs { dt };
+
+        while !game.victory && !game.player.crashed && game.play_time < 60.0 {
+            game.update(&args);
+        }
+
+        game.crash_report
+    }
+
+    #[test]
+    fn open_floor_kills_after_falling_out_of_the_level() {
+        let report: CrashReport =
+            play(&["16,600,none,162", "1,5000,868,50,50"], 1.0 / 120.0, false)
+                .expect("fell without crashing");
+
+        assert_eq!(report.kind, "section");
+        assert_eq!(report.line, 1);
+        assert!(matches!(report.contact, Contact::OutOfBounds));
+        assert!(report.pos.y > GROUND_Y_NORMAL + FALL_MARGIN);
+    }
+
+    #[test]
+    fn open_ceiling_only_kills_under_flipped_gravity() {
+        let level: [&str; 2] = ["16,600,918,none", "1,3000,400,50,50"];
+
+        assert!(play(&level, 1.0 / 120.0, false).is_none());
+        assert!(play(&level, 1.0 / 120.0, true)
+            .is_some_and(|report| matches!(report.contact, Contact::OutOfBounds)));
+    }
+
+    #[test]
+    fn loads_reuse_the_physics_file_read_at_creation() {
+        let mut game: Game = Game::new();
+        game.physics_config = Some(Config::parse(
+            "[default]\nspeed = 700\n[slow]\nspeed = 300\n",
         ));
 
-        x += (g.advance().x >> 6) as i32;
-        y += (g.advance().y >> 6) as i32;
+        game.load_level(&lines(&["1,3000,400,50,50"]));
+        assert_eq!(game.physics.speed, 700.0);
+
+        game.load_level(&lines(&["0,physics,slow,gravity=2", "1,3000,400,50,50"]));
+        assert_eq!((game.physics.speed, game.physics.gravity), (300.0, 2.0));
     }
-    res
-}
 
-fn render_text(
-    color: [f32; 4],
-    text: &str,
-    x: i32,
-    y: i32,
-    text_height: u32,
-    gl: &mut GlGraphics,
-    c: Context,
-) {
-    let assets: PathBuf = Search::ParentsThenKids(3, 3).for_folder("assets").unwrap();
-    let freetype: Library = Library::init().unwrap();
-    let font: PathBuf = assets.join("CaskaydiaCoveNerdFontCompleteRegular.otf");
-    let mut face: Face = freetype.new_face(font, 0).unwrap();
-    face.set_pixel_sizes(0, text_height).unwrap();
-    let glyphs_text: &Vec<(Texture, [f64; 2])> = &glyphs(&mut face, text, x, y);
-
-    for &(ref texture, [x, y]) in glyphs_text {
-        use graphics::*;
+    #[test]
+    fn elastic_camera_zoom_overshoots_and_settles() {
+        let mut game: Game = Game::new();
+        game.load_level(&lines(&["12,0,2,1,elastic_out", "1,3000,400,50,50"]));
+        game.frozen = false;
+
+        let args: UpdateArgs = UpdateArgs { dt: 1.0 / 120.0 };
+        let mut highest: f32 = 0.0;
+
+        for _ in 0..240 {
+            game.update(&args);
+            highest = highest.max(game.camera.zoom);
+        }
+
+        assert!(highest > 2.0);
+        assert_eq!(game.camera.zoom, 2.0);
+    }
+
+    const BLOCK_TEST: [&str; 12] = [
+        "1,1824,540,50,25",
+        "1,1920,360,10,50",
+        "1,1440,270,100,75",
+        "1,2880,868,150,50",
+        "1,3640,864,150,50",
+        "1,3640,664,150,50",
+        "1,3240,664,150,50",
+        "1,3440,814,150,50",
+        "1,3240,764,150,50",
+        "1,4000,490,1000,70",
+        "1,5150,490,1000,70",
+        "1,6250,490,1000,70",
+    ];
+
+    // Same crash whatever the update rate, since collisions are swept rather than sampled
+
+    fn assert_same_crash(level: &[&str], jump: bool, kind: &str, line: usize, contact: Contact) {
+        for dt in [1.0 / 30.0, 1.0 / 60.0, 1.0 / 240.0] {
+            let report: CrashReport =
+                play_holding(level, dt, false, jump).expect("finished without crashing");
+
+            assert_eq!(
+                (report.kind, report.line, &report.contact),
+                (kind, line, &contact),
+                "dt {}",
+                dt
+            );
+        }
+    }
+
+    #[test]
+    fn crash_is_the_same_at_every_update_rate() {
+        assert_same_crash(&BLOCK_TEST, false, "block", 4, Contact::Side(Side::Left));
+        assert_same_crash(&BLOCK_TEST, true, "block", 9, Contact::Side(Side::Bottom));
+        assert_same_crash(
+            &[
+                "2,1750,162,50,50,false",
+                "2,1750,918,50,50,true",
+                "2,2500,918,250,130,true",
+            ],
+            false,
+            "spike",
+            2,
+            Contact::SpikeEdge(0),
+        );
+    }
+
+    #[test]
+    fn thin_block_is_hit_at_every_update_rate() {
+        // The 10 wide block from block_test, moved down into the player's path. At 30 updates a
+        // second the player moves twice its width per tick
+        assert_same_crash(
+            &["1,1920,818,10,100"],
+            false,
+            "block",
+            1,
+            Contact::Side(Side::Left),
+        );
+    }
+
+    #[test]
+    fn attempts_remember_starting_at_a_start_position() {
+        let mut game: Game = Game::new();
+
+        game.load_level(&lines(&["1,3000,868,50,50", "17,2000,893,cube,normal,1,1"]));
+        assert!(game.from_start_position);
+        assert_eq!(game.player.pos.x, 2000.0);
+
+        game.load_level(&lines(&[
+            "1,3000,868,50,50",
+            "17,2000,893,cube,normal,1,1,enabled=false",
+        ]));
+        assert!(!game.from_start_position);
+    }
+
+    #[test]
+    fn unknown_start_position_mode_plays_as_a_cube() {
+        let mut game: Game = Game::new();
 
-        Image::new_color(color).draw(texture, &c.draw_state, c.transform.trans(x, y), gl);
+        game.load_level(&lines(&["1,3000,868,50,50", "17,2000,893,ship,normal,1,1"]));
+        assert!(game.from_start_position);
+        assert_eq!(game.player.pos.x, 2000.0);
     }
 }
@@ -0,0 +1,68 @@
+use crate::collider::Collider;
+use crate::color::ColorChannels;
+use crate::crash_report::Contact;
+use crate::player::Player;
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::vector::Vector;
+use opengl_graphics::GlGraphics;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Interaction {
+    // Can be landed on, crashes the player once their solid hitbox touches it
+    Solid,
+    // Crashes the player on any contact
+    Hazard,
+    // Calls `on_touch` every tick the player overlaps it
+    Touch,
+    // Never collides with the player
+    Passive,
+}
+
+// Everything placed in a level. `Game::update` only talks to objects through this trait, so a new
+// kind of object only needs an implementation, a `parse` constructor for its level line and an
+// entry type in `Game::load_level`
+
+pub trait GameObject {
+    fn name(&self) -> &'static str;
+
+    fn props(&self) -> &Properties;
+
+    fn props_mut(&mut self) -> &mut Properties;
+
+    fn interaction(&self) -> Interaction;
+
+    fn collider(&self) -> Collider;
+
+    // Drawn extent, which can be larger than the collider
+
+    fn bounds(&self) -> Rect;
+
+    fn center(&self) -> Vector;
+
+    // Horizontal span covered by either the drawn shape or the collider, whichever is wider
+
+    fn x_extent(&self) -> (f32, f32) {
+        let bounds: Rect = self.bounds().union(&self.collider().bounds());
+
+        (bounds.pos.x, bounds.max().x)
+    }
+
+    fn translate(&mut self, delta: Vector);
+
+    // Turns the object by `degrees`, around `pivot` when given or in place otherwise
+
+    fn rotate(&mut self, pivot: Option<Vector>, degrees: f32);
+
+    fn render(&self, colors: &ColorChannels, transform: [[f64; 3]; 2], gl: &mut GlGraphics);
+
+    fn on_touch(&mut self, _player: &mut Player) {}
+
+    fn contact(&self, _player: &Player) -> Contact {
+        Contact::Inside
+    }
+
+    fn collision_id(&self) -> Option<u32> {
+        None
+    }
+}
@@ -0,0 +1,93 @@
+use crate::input::GamepadEvent;
+#[cfg(feature = "gamepad")]
+use gilrs::{Button, EventType, Gilrs};
+
+// Controllers are read through gilrs when the game is built with the `gamepad` feature, without
+// it there never are any. Plugging controllers in and out works while the game runs
+
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<Gilrs>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Gamepads {
+            #[cfg(feature = "gamepad")]
+            gilrs: match Gilrs::new() {
+                Ok(gilrs) => Some(gilrs),
+                Err(error) => {
+                    eprintln!("controllers are unavailable: {}", error);
+                    None
+                }
+            },
+        }
+    }
+
+    // Events since the last call
+
+    #[cfg(feature = "gamepad")]
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        let mut events: Vec<GamepadEvent> = Vec::new();
+        let Some(gilrs) = &mut self.gilrs else {
+            return events;
+        };
+
+        while let Some(event) = gilrs.next_event() {
+            let id: usize = event.id.into();
+
+            match event.event {
+                EventType::ButtonPressed(button, _) => {
+                    events.extend(
+                        button_name(button)
+                            .map(|name| GamepadEvent::Button(id, name.to_string(), true)),
+                    );
+                }
+                EventType::ButtonReleased(button, _) => {
+                    events.extend(
+                        button_name(button)
+                            .map(|name| GamepadEvent::Button(id, name.to_string(), false)),
+                    );
+                }
+                EventType::Connected => events.push(GamepadEvent::Connected(id)),
+                EventType::Disconnected => events.push(GamepadEvent::Disconnected(id)),
+                _ => {}
+            }
+        }
+
+        events
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        Vec::new()
+    }
+}
+
+// Names used in the bindings, after the button's position on the controller
+
+#[cfg(feature = "gamepad")]
+fn button_name(button: Button) -> Option<&'static str> {
+    match button {
+        Button::South => Some("south"),
+        Button::East => Some("east"),
+        Button::North => Some("north"),
+        Button::West => Some("west"),
+        Button::C => Some("c"),
+        Button::Z => Some("z"),
+        Button::LeftTrigger => Some("left_bumper"),
+        Button::LeftTrigger2 => Some("left_trigger"),
+        Button::RightTrigger => Some("right_bumper"),
+        Button::RightTrigger2 => Some("right_trigger"),
+        Button::Select => Some("select"),
+        Button::Start => Some("start"),
+        Button::Mode => Some("mode"),
+        Button::LeftThumb => Some("left_stick"),
+        Button::RightThumb => Some("right_stick"),
+        Button::DPadUp => Some("dpad_up"),
+        Button::DPadDown => Some("dpad_down"),
+        Button::DPadLeft => Some("dpad_left"),
+        Button::DPadRight => Some("dpad_right"),
+        Button::Unknown => None,
+    }
+}
@@ -0,0 +1,161 @@
+use crate::constants::UPS;
+use crate::game::Game;
+use piston::input::UpdateArgs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Instant;
+
+// Ten minutes of game time, enough for any level that doesn't loop forever
+const MAX_TICKS: u64 = UPS * 600;
+
+const BENCH_TICKS: u64 = UPS * 20;
+
+// Jump ranges and gravity flips from an input script, by tick
+
+#[derive(Default)]
+struct Script {
+    jumps: Vec<(u64, u64)>,
+    flips: Vec<u64>,
+}
+
+// Runs a level without a window at a fixed update rate and prints how the attempt ended.
+// The optional input script holds one action per line, either `jump,start_tick,end_tick` to
+// hold jump over a range of ticks or `flip,tick` to flip gravity
+
+pub fn run(level_name: &str, input_path: Option<&str>) {
+    let script: Script = match input_path {
+        Some(input_path) => match read_script(Path::new(input_path)) {
+            Some(script) => script,
+            None => return,
+        },
+        None => Script::default(),
+    };
+
+    let mut game: Game = Game::new();
+
+    if let Err(error) = game.initialize_level(level_name) {
+        eprintln!("{}", error);
+        return;
+    }
+
+    game.frozen = false;
+
+    let args: UpdateArgs = UpdateArgs {
+        dt: 1.0 / UPS as f64,
+    };
+
+    while game.tick < MAX_TICKS {
+        let tick: u64 = game.tick + 1;
+        game.player.jumping = script
+            .jumps
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&tick));
+
+        if script.flips.contains(&tick) {
+            game.flip_gravity();
+        }
+
+        if let Some(report) = game.update(&args) {
+            println!("crash: {}", report);
+            return;
+        }
+
+        if game.victory {
+            println!("victory at tick {}", game.tick);
+            return;
+        }
+    }
+
+    println!("timed out after {} ticks", game.tick);
+}
+
+// None after printing what is wrong with the script
+
+fn read_script(path: &Path) -> Option<Script> {
+    let file: File = match File::open(path) {
+        Ok(file) => file,
+        Err(error) => {
+            eprintln!("Failed to open input script {}: {}", path.display(), error);
+            return None;
+        }
+    };
+
+    let mut script: Script = Script::default();
+
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line: String = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("Failed to read input script {}: {}", path.display(), error);
+                return None;
+            }
+        };
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        let tick = |field: usize| fields.get(field).and_then(|x| x.parse::<u64>().ok());
+
+        let valid: bool = match (fields[0], tick(1), tick(2)) {
+            ("jump", Some(start), Some(end)) => {
+                script.jumps.push((start, end));
+                true
+            }
+            ("flip", Some(tick), _) => {
+                script.flips.push(tick);
+                true
+            }
+            ("jump" | "flip", _, _) => false,
+            _ => true,
+        };
+
+        if !valid {
+            eprintln!(
+                "Invalid line {} in input script {}: `{}`",
+                i + 1,
+                path.display(),
+                line
+            );
+            return None;
+        }
+    }
+
+    Some(script)
+}
+
+// Times the simulation on generated levels of growing size. With the spatial index the cost per
+// tick should stay flat no matter how many objects the level holds
+
+pub fn bench() {
+    let args: UpdateArgs = UpdateArgs {
+        dt: 1.0 / UPS as f64,
+    };
+
+    for count in [1_000, 10_000, 100_000] {
+        // A row of blocks and spikes overhead, out of reach of a player who never jumps
+        let lines: Vec<String> = (0..count)
+            .map(|i| {
+                if i % 2 == 0 {
+                    format!("1,{},100,50,50", i * 100)
+                } else {
+                    format!("2,{},100,50,50,false", i * 100)
+                }
+            })
+            .collect();
+
+        let mut game: Game = Game::new();
+        game.load_level(&lines);
+        game.frozen = false;
+
+        let start: Instant = Instant::now();
+
+        while game.tick < BENCH_TICKS && !game.player.crashed {
+            game.update(&args);
+        }
+
+        println!(
+            "{} objects: {:.2} us per tick over {} ticks",
+            count,
+            start.elapsed().as_secs_f64() * 1e6 / game.tick as f64,
+            game.tick
+        );
+    }
+}
@@ -0,0 +1,458 @@
+use piston::{Button, Key};
+use std::collections::HashSet;
+
+// Gameplay actions the player can bind inputs to. Menus and the editor keep their own keys
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Jump,
+    FlipGravity,
+    Pause,
+    Restart,
+    PlaceCheckpoint,
+    RemoveCheckpoint,
+}
+
+pub const ACTIONS: [Action; 6] = [
+    Action::Jump,
+    Action::FlipGravity,
+    Action::Pause,
+    Action::Restart,
+    Action::PlaceCheckpoint,
+    Action::RemoveCheckpoint,
+];
+
+impl Action {
+    // Key in the `[bindings]` section of the settings file
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Jump => "jump",
+            Action::FlipGravity => "flip_gravity",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::PlaceCheckpoint => "place_checkpoint",
+            Action::RemoveCheckpoint => "remove_checkpoint",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Jump => "Jump",
+            Action::FlipGravity => "Flip gravity",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::PlaceCheckpoint => "Place checkpoint",
+            Action::RemoveCheckpoint => "Remove checkpoint",
+        }
+    }
+
+    fn defaults(self) -> &'static [&'static str] {
+        match self {
+            Action::Jump => &["mouse_left", "space", "up", "pad_south"],
+            Action::FlipGravity => &["mouse_right", "pad_east"],
+            Action::Pause => &["escape", "pad_start"],
+            Action::Restart => &["r", "pad_select"],
+            Action::PlaceCheckpoint => &["z", "pad_north"],
+            Action::RemoveCheckpoint => &["x", "pad_west"],
+        }
+    }
+}
+
+// Inputs are named by strings like "space", "r" or "mouse_left", so the mapping doesn't depend on
+// where the events come from. `input_name` gives the name of a Piston button.
+//
+// Controller buttons are named "pad<id>_<button>", like "pad0_south". A binding to "pad_south"
+// matches that button on every controller, "pad1_south" only on controller 1
+
+pub fn input_name(button: Button) -> Option<String> {
+    match button {
+        Button::Keyboard(key) => Some(format!("{:?}", key).to_lowercase()),
+        Button::Mouse(mouse_button) => Some(format!("mouse_{:?}", mouse_button).to_lowercase()),
+        _ => None,
+    }
+}
+
+// Controller buttons that stand in for menu keys, so the menus work without a keyboard. East and
+// start act as Escape, which goes back and also resumes from the pause menu
+
+pub fn menu_key(button: &str) -> Option<Key> {
+    match button {
+        "dpad_up" => Some(Key::Up),
+        "dpad_down" => Some(Key::Down),
+        "dpad_left" => Some(Key::Left),
+        "dpad_right" => Some(Key::Right),
+        "south" => Some(Key::Return),
+        "east" | "start" => Some(Key::Escape),
+        _ => None,
+    }
+}
+
+// "pad_south" for "pad1_south", None for inputs that aren't on a controller
+
+fn any_pad(input: &str) -> Option<String> {
+    let rest: &str = input.strip_prefix("pad")?;
+    let (id, button): (&str, &str) = rest.split_once('_')?;
+
+    (!id.is_empty() && id.chars().all(|x| x.is_ascii_digit())).then(|| format!("pad_{}", button))
+}
+
+// Controller events, from whichever library reads the controllers
+
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum GamepadEvent {
+    Connected(usize),
+    Disconnected(usize),
+    // Controller, button name and whether it was pressed
+    Button(usize, String, bool),
+}
+
+// Every action's inputs, any one of which triggers it
+
+#[derive(Clone)]
+pub struct Bindings {
+    pub actions: Vec<(Action, Vec<String>)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            actions: ACTIONS
+                .iter()
+                .map(|&action| {
+                    (
+                        action,
+                        action.defaults().iter().map(|x| x.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn new() -> Self {
+        Bindings {
+            ..Default::default()
+        }
+    }
+
+    // The defaults with the actions in `pairs` replaced, each value is a comma separated list
+    // of input names. Actions left out keep their default inputs
+
+    pub fn parse(pairs: &[(String, String)]) -> Self {
+        let mut bindings: Bindings = Bindings::new();
+
+        for (key, value) in pairs {
+            let Some(action) = ACTIONS.iter().find(|action| action.name() == key) else {
+                eprintln!("unknown action {} in the bindings", key);
+                continue;
+            };
+
+            bindings.set(
+                *action,
+                value
+                    .split(',')
+                    .map(|x| x.trim().to_lowercase())
+                    .filter(|x| !x.is_empty())
+                    .collect(),
+            );
+        }
+
+        bindings
+    }
+
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        self.actions
+            .iter()
+            .map(|(action, inputs)| (action.name().to_string(), inputs.join(", ")))
+            .collect()
+    }
+
+    pub fn get(&self, action: Action) -> &[String] {
+        self.actions
+            .iter()
+            .find(|(x, _)| *x == action)
+            .map_or(&[], |(_, inputs)| inputs.as_slice())
+    }
+
+    // For on-screen hints, like "space / up"
+
+    pub fn describe(&self, action: Action) -> String {
+        self.get(action).join(" / ")
+    }
+
+    pub fn set(&mut self, action: Action, inputs: Vec<String>) {
+        if let Some((_, x)) = self.actions.iter_mut().find(|(x, _)| *x == action) {
+            *x = inputs;
+        }
+    }
+
+    // Binds `input` to `action`, or unbinds it if it already is. The last input of an action
+    // can't be removed, so nothing becomes unreachable
+
+    pub fn toggle(&mut self, action: Action, input: &str) {
+        let mut inputs: Vec<String> = self.get(action).to_vec();
+
+        if let Some(i) = inputs.iter().position(|x| x == input) {
+            if inputs.len() > 1 {
+                inputs.remove(i);
+            }
+        } else {
+            inputs.push(input.to_string());
+        }
+
+        self.set(action, inputs);
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        self.set(
+            action,
+            action.defaults().iter().map(|x| x.to_string()).collect(),
+        );
+    }
+}
+
+// Which inputs are held, turning input presses and releases into action presses and releases.
+// An action stays held while any of its inputs is, so releasing Space while still holding the
+// mouse doesn't stop a jump
+
+#[derive(Default)]
+pub struct Controls {
+    held: HashSet<String>,
+}
+
+impl Controls {
+    pub fn new() -> Self {
+        Controls {
+            ..Default::default()
+        }
+    }
+
+    pub fn held(&self, bindings: &Bindings, action: Action) -> bool {
+        bindings.get(action).iter().any(|input| {
+            self.held.contains(input)
+                || self
+                    .held
+                    .iter()
+                    .any(|held| any_pad(held).as_deref() == Some(input))
+        })
+    }
+
+    // Actions that were pressed (true) or released (false) by this input event. Key repeats
+    // don't press anything again
+
+    pub fn event(
+        &mut self,
+        bindings: &Bindings,
+        input: &str,
+        pressed: bool,
+    ) -> Vec<(Action, bool)> {
+        self.change(bindings, |held| {
+            if pressed {
+                held.insert(input.to_string());
+            } else {
+                held.remove(input);
+            }
+        })
+    }
+
+    // Unplugging a controller releases everything held on it, so a jump can't get stuck
+
+    pub fn gamepad_event(
+        &mut self,
+        bindings: &Bindings,
+        event: &GamepadEvent,
+    ) -> Vec<(Action, bool)> {
+        match event {
+            GamepadEvent::Connected(_) => Vec::new(),
+            GamepadEvent::Disconnected(id) => {
+                let prefix: String = format!("pad{}_", id);
+
+                self.change(bindings, |held| {
+                    held.retain(|input| !input.starts_with(&prefix))
+                })
+            }
+            GamepadEvent::Button(id, button, pressed) => {
+                self.event(bindings, &format!("pad{}_{}", id, button), *pressed)
+            }
+        }
+    }
+
+    fn change(
+        &mut self,
+        bindings: &Bindings,
+        f: impl FnOnce(&mut HashSet<String>),
+    ) -> Vec<(Action, bool)> {
+        let before: Vec<bool> = ACTIONS
+            .iter()
+            .map(|&action| self.held(bindings, action))
+            .collect();
+
+        f(&mut self.held);
+
+        ACTIONS
+            .iter()
+            .zip(before)
+            .filter(|&(&action, was_held)| self.held(bindings, action) != was_held)
+            .map(|(&action, was_held)| (action, !was_held))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn action_stays_held_while_any_input_is() {
+        let bindings: Bindings = Bindings::new();
+        let mut controls: Controls = Controls::new();
+
+        assert_eq!(
+            controls.event(&bindings, "space", true),
+            [(Action::Jump, true)]
+        );
+        assert!(controls.event(&bindings, "mouse_left", true).is_empty());
+        assert!(controls.event(&bindings, "space", false).is_empty());
+        assert!(controls.held(&bindings, Action::Jump));
+        assert_eq!(
+            controls.event(&bindings, "mouse_left", false),
+            [(Action::Jump, false)]
+        );
+        assert!(!controls.held(&bindings, Action::Jump));
+    }
+
+    #[test]
+    fn key_repeats_do_not_press_again() {
+        let bindings: Bindings = Bindings::new();
+        let mut controls: Controls = Controls::new();
+
+        assert_eq!(
+            controls.event(&bindings, "r", true),
+            [(Action::Restart, true)]
+        );
+        assert!(controls.event(&bindings, "r", true).is_empty());
+        assert_eq!(
+            controls.event(&bindings, "r", false),
+            [(Action::Restart, false)]
+        );
+        // Unbound inputs and releases of inputs that were never pressed change nothing
+        assert!(controls.event(&bindings, "q", true).is_empty());
+        assert!(controls.event(&bindings, "space", false).is_empty());
+    }
+
+    #[test]
+    fn parse_replaces_only_the_actions_given() {
+        let bindings: Bindings =
+            Bindings::parse(&pairs(&[("jump", " W, Mouse_Left ,, "), ("dance", "d")]));
+
+        assert_eq!(bindings.get(Action::Jump), ["w", "mouse_left"]);
+        assert_eq!(bindings.get(Action::Pause), Action::Pause.defaults());
+        assert_eq!(bindings.actions.len(), ACTIONS.len());
+        assert_eq!(
+            Bindings::parse(&bindings.pairs()).get(Action::Jump),
+            bindings.get(Action::Jump)
+        );
+    }
+
+    #[test]
+    fn toggle_never_removes_the_last_input() {
+        let mut bindings: Bindings = Bindings::parse(&pairs(&[("restart", "r")]));
+
+        bindings.toggle(Action::Restart, "r");
+        assert_eq!(bindings.get(Action::Restart), ["r"]);
+
+        bindings.toggle(Action::Restart, "backspace");
+        assert_eq!(bindings.get(Action::Restart), ["r", "backspace"]);
+
+        bindings.toggle(Action::Restart, "r");
+        assert_eq!(bindings.get(Action::Restart), ["backspace"]);
+
+        bindings.reset(Action::Restart);
+        assert_eq!(bindings.get(Action::Restart), Action::Restart.defaults());
+    }
+
+    fn button(id: usize, button: &str, pressed: bool) -> GamepadEvent {
+        GamepadEvent::Button(id, button.to_string(), pressed)
+    }
+
+    #[test]
+    fn any_pad_binding_matches_every_controller() {
+        let bindings: Bindings = Bindings::new();
+        let mut controls: Controls = Controls::new();
+
+        assert_eq!(
+            controls.gamepad_event(&bindings, &button(0, "south", true)),
+            [(Action::Jump, true)]
+        );
+        assert!(controls
+            .gamepad_event(&bindings, &button(3, "south", true))
+            .is_empty());
+        assert!(controls
+            .gamepad_event(&bindings, &button(0, "south", false))
+            .is_empty());
+        assert_eq!(
+            controls.gamepad_event(&bindings, &button(3, "south", false)),
+            [(Action::Jump, false)]
+        );
+    }
+
+    #[test]
+    fn numbered_pad_binding_matches_only_that_controller() {
+        let bindings: Bindings = Bindings::parse(&pairs(&[("jump", "pad1_south")]));
+        let mut controls: Controls = Controls::new();
+
+        assert!(controls
+            .gamepad_event(&bindings, &button(0, "south", true))
+            .is_empty());
+        assert!(controls
+            .gamepad_event(&bindings, &button(11, "south", true))
+            .is_empty());
+        assert_eq!(
+            controls.gamepad_event(&bindings, &button(1, "south", true)),
+            [(Action::Jump, true)]
+        );
+    }
+
+    #[test]
+    fn unplugging_releases_what_the_controller_held() {
+        let bindings: Bindings = Bindings::new();
+        let mut controls: Controls = Controls::new();
+
+        controls.gamepad_event(&bindings, &button(2, "east", true));
+        controls.gamepad_event(&bindings, &button(2, "south", true));
+        controls.gamepad_event(&bindings, &button(0, "start", true));
+        controls.event(&bindings, "space", true);
+
+        // Jump is still held on the keyboard and pause on the other controller
+        assert_eq!(
+            controls.gamepad_event(&bindings, &GamepadEvent::Disconnected(2)),
+            [(Action::FlipGravity, false)]
+        );
+        assert!(controls.held(&bindings, Action::Jump));
+        assert!(controls.held(&bindings, Action::Pause));
+        assert_eq!(
+            controls.event(&bindings, "space", false),
+            [(Action::Jump, false)]
+        );
+        assert!(controls
+            .gamepad_event(&bindings, &GamepadEvent::Connected(2))
+            .is_empty());
+    }
+
+    #[test]
+    fn pad_buttons_stand_in_for_menu_keys() {
+        assert_eq!(menu_key("south"), Some(Key::Return));
+        assert_eq!(menu_key("start"), Some(Key::Escape));
+        assert_eq!(menu_key("dpad_down"), Some(Key::Down));
+        assert_eq!(menu_key("left_trigger"), None);
+    }
+}
@@ -1,43 +1,142 @@
+mod assets;
 mod block;
+mod camera;
+mod collider;
+mod collision_block;
+mod color;
+mod config;
 mod constants;
+mod crash_report;
+mod easing;
+mod editor;
 mod game;
+mod game_object;
+mod gamepad;
+mod headless;
+mod input;
+mod menu;
+mod menu_rows;
 mod orb;
+mod pause_menu;
+mod physics;
 mod player;
+mod progress;
+mod properties;
+mod rect;
+mod screen;
+mod section;
+mod segment;
+mod settings;
+mod settings_menu;
+mod side;
+mod spatial_index;
 mod spike;
+mod start_position;
 mod surface_result;
+mod text;
+mod trigger;
 mod util;
 mod vector;
 
-use crate::constants::{HEIGHT, WIDTH};
+use crate::assets::{AssetError, Assets};
+use crate::constants::FONT_FILE;
+use crate::editor::Editor;
 use crate::game::Game;
-use constants::{FPS, GROUND_Y_FLIP, GROUND_Y_NORMAL, SELECTED_LEVEL};
+use crate::gamepad::Gamepads;
+use crate::input::{input_name, menu_key, Action, Controls, GamepadEvent};
+use crate::menu::{Menu, MenuAction};
+use crate::pause_menu::{PauseAction, PauseMenu};
+use crate::progress::{today, LevelProgress, Progress};
+use crate::screen::Screen;
+use crate::settings::Settings;
+use crate::settings_menu::SettingsMenu;
+use crate::text::Text;
+use constants::{SELECTED_LEVEL, UPS};
 use glutin_window::GlutinWindow as Window;
-use opengl_graphics::OpenGL;
+use graphics::Viewport;
+use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderEvent, UpdateEvent};
-use piston::{Button, ButtonEvent, ButtonState, EventLoop, Key, MouseButton, WindowSettings};
+use piston::input::{MouseCursorEvent, MouseScrollEvent, RenderEvent, ResizeEvent, UpdateEvent};
+use piston::{AdvancedWindow, Button, ButtonEvent, ButtonState, EventLoop, Key, WindowSettings};
 use std::time::{Duration, Instant};
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    if args.get(1).map(|x| x.as_str()) == Some("--headless") {
+        headless::run(
+            args.get(2).map_or(SELECTED_LEVEL, |x| x.as_str()),
+            args.get(3).map(|x| x.as_str()),
+        );
+        return;
+    }
+
+    if args.get(1).map(|x| x.as_str()) == Some("--bench") {
+        headless::bench();
+        return;
+    }
+
     let opengl: OpenGL = OpenGL::V4_5;
+    let mut settings: Settings = Settings::load();
 
-    let mut window: Window = WindowSettings::new("Geometry Dash", [WIDTH, HEIGHT])
-        .graphics_api(opengl)
-        .resizable(false)
-        .exit_on_esc(false)
-        .vsync(true)
-        .samples(4)
-        .build()
-        .unwrap();
+    let mut window: Window =
+        WindowSettings::new("Geometry Dash", [settings.width, settings.height])
+            .graphics_api(opengl)
+            .resizable(true)
+            .exit_on_esc(false)
+            .fullscreen(settings.fullscreen)
+            .vsync(settings.vsync)
+            .samples(settings.msaa)
+            .build()
+            .unwrap();
 
+    let mut gl: GlGraphics = GlGraphics::new(opengl);
+    let mut font: Text = match Assets::new()
+        .find(FONT_FILE)
+        .and_then(|path| Text::load(&path))
+    {
+        Ok(font) => font,
+        Err(error) => {
+            eprintln!("{}, using the built-in font", error);
+            Text::embedded()
+        }
+    };
     let mut game: Game = Game::new();
+    let mut editor: Option<Editor> = None;
+    // The editor whose level is being playtested, kept with its unsaved changes and undo history
+    let mut playtested: Option<Editor> = None;
+    let mut menu: Option<Menu> = None;
+    let mut pause: Option<PauseMenu> = None;
+    let mut settings_menu: Option<SettingsMenu> = None;
+    let mut progress: Progress = Progress::load();
     let mut fps: i32 = 0;
     let mut fps_counter: i32 = 0;
     let mut last_update: Instant = Instant::now();
+    let mut screen: Screen = Screen::default();
+    let mut resolution: [u32; 2] = [settings.width, settings.height];
+    let mut controls: Controls = Controls::new();
+    let mut gamepads: Gamepads = Gamepads::new();
+
+    if args.get(1).map(|x| x.as_str()) == Some("--edit") {
+        let level_name: &str = args.get(2).map_or(SELECTED_LEVEL, |x| x.as_str());
 
-    game.initialize_level(SELECTED_LEVEL);
+        match game
+            .initialize_level(level_name)
+            .and_then(|_| Editor::new(level_name))
+        {
+            Ok(open) => editor = Some(open),
+            Err(error) => {
+                eprintln!("{}", error);
+                return;
+            }
+        }
+    } else {
+        menu = Some(Menu::new());
+    }
 
-    let mut events: Events = Events::new(EventSettings::new()).max_fps(FPS);
+    let mut events: Events = Events::new(EventSettings::new())
+        .max_fps(settings.fps_cap)
+        .ups(UPS);
 
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
@@ -51,57 +150,372 @@ fn main() {
                 last_update = now;
             }
 
-            game.render(&args, fps);
-        }
+            screen = Screen::new(args.window_size, args.draw_size);
 
-        if let Some(args) = e.update_args() {
-            if !game.frozen {
-                game.update(&args);
+            let viewport: Viewport = screen.viewport();
+
+            gl.draw(screen.window_viewport(), |_, gl| {
+                graphics::clear([0.0, 0.0, 0.0, 1.0], gl);
+            });
+
+            if let Some(menu) = &menu {
+                menu.render(&mut gl, &mut font, viewport, &progress);
+            } else if let Some(editor) = &editor {
+                editor.render(&mut gl, &mut font, viewport);
+            } else {
+                game.render(&mut gl, &mut font, viewport, fps, &settings);
+
+                if let Some(pause) = &pause {
+                    pause.render(
+                        &mut gl,
+                        &mut font,
+                        viewport,
+                        game.practice,
+                        &settings.bindings,
+                    );
+                }
+            }
+
+            if let Some(settings_menu) = &settings_menu {
+                settings_menu.render(&mut gl, &mut font, viewport, &settings);
             }
         }
 
-        if let Some(args) = e.button_args() {
-            let state: ButtonState = args.state;
-            let button: Button = args.button;
+        if let Some(args) = e.resize_args() {
+            screen = Screen::new(args.window_size, args.draw_size);
+        }
 
-            match button {
-                Button::Mouse(mouse_button) => match mouse_button {
-                    MouseButton::Left => {
-                        if !game.frozen {
-                            game.player.jumping = state == ButtonState::Press;
-                        }
+        // Menus and the editor get the mouse in world units on the view
+        let mouse: Option<[f64; 2]> = e.mouse_cursor_args().map(|pos| screen.view_position(pos));
+
+        // Every input goes through the controls, even while a menu has it, so they always know
+        // what is held. The actions are only used by the game
+        let mut actions: Vec<(Action, bool)> = e
+            .button_args()
+            .and_then(|args| {
+                input_name(args.button).map(|input| {
+                    controls.event(&settings.bindings, &input, args.state == ButtonState::Press)
+                })
+            })
+            .unwrap_or_default();
+
+        // Buttons for the menus, with controller buttons standing in for their keys
+        let mut buttons: Vec<(Button, ButtonState)> = e
+            .button_args()
+            .map(|args| (args.button, args.state))
+            .into_iter()
+            .collect();
+
+        // Controllers are polled once per update. Their buttons are bound as "pad_<button>" from
+        // the settings screen, which matches every controller
+        if e.update_args().is_some() {
+            for event in gamepads.poll() {
+                // The number is the one used to bind a single controller, like "pad1_south"
+                match &event {
+                    GamepadEvent::Connected(id) => println!("controller {} connected", id),
+                    GamepadEvent::Disconnected(id) => println!("controller {} disconnected", id),
+                    GamepadEvent::Button(..) => {}
+                }
+
+                let captured: bool = match (&mut settings_menu, &event) {
+                    (Some(open), GamepadEvent::Button(_, button, true)) => {
+                        open.capture(&format!("pad_{}", button), &mut settings)
                     }
-                    MouseButton::Right => {
-                        if state == ButtonState::Press && !game.frozen && !game.player.crashed {
-                            game.player.gravity_flip = !game.player.gravity_flip;
-                            game.player.acc.y = -game.player.acc.y;
-                            game.player.grounded = false;
-                            game.player.jump.y = if game.player.gravity_flip {
-                                GROUND_Y_FLIP
+                    _ => false,
+                };
+
+                if let (false, GamepadEvent::Button(_, button, pressed)) = (captured, &event) {
+                    if let Some(key) = menu_key(button) {
+                        buttons.push((
+                            Button::Keyboard(key),
+                            if *pressed {
+                                ButtonState::Press
                             } else {
-                                GROUND_Y_NORMAL
-                            }
-                        }
+                                ButtonState::Release
+                            },
+                        ));
                     }
-                    _ => {}
-                },
-                Button::Keyboard(keyboard_button) => match keyboard_button {
-                    Key::Escape => {
-                        if state == ButtonState::Press && !game.player.crashed && !game.victory {
-                            game.frozen = !game.frozen;
+                }
+
+                actions.extend(controls.gamepad_event(&settings.bindings, &event));
+            }
+        }
+
+        if let Some(open) = &mut settings_menu {
+            if let Some(pos) = mouse {
+                open.mouse_move(pos);
+            }
+
+            if buttons
+                .iter()
+                .any(|&(button, state)| open.button(button, state, &mut settings))
+            {
+                events.set_max_fps(settings.fps_cap);
+
+                // Only a new resolution resizes the window, so closing the screen doesn't
+                // undo resizing it by hand
+                if resolution != [settings.width, settings.height] {
+                    resolution = [settings.width, settings.height];
+                    window.set_size(resolution);
+                }
+
+                settings_menu = None;
+
+                if let Err(error) = settings.save() {
+                    eprintln!("failed to save settings: {}", error);
+                }
+            }
+
+            continue;
+        }
+
+        if let Some(open) = &mut menu {
+            if let Some(pos) = mouse {
+                open.mouse_move(pos);
+            }
+
+            if let Some(amount) = e.mouse_scroll_args() {
+                open.scroll(amount);
+            }
+
+            match buttons
+                .iter()
+                .find_map(|&(button, state)| open.button(button, state))
+            {
+                Some(MenuAction::Play(level_name)) => {
+                    game = Game::new();
+
+                    match start_attempt(&mut game, &mut progress, &level_name) {
+                        Ok(()) => {
+                            menu = None;
+                            playtested = None;
                         }
+                        Err(error) => eprintln!("{}", error),
                     }
-                    Key::R => {
-                        if state == ButtonState::Press
-                            && (game.frozen || game.player.crashed || game.victory)
-                        {
-                            game.initialize_level(SELECTED_LEVEL);
+                }
+                Some(MenuAction::Settings) => settings_menu = Some(SettingsMenu::new()),
+                Some(MenuAction::Quit) => break,
+                None => {}
+            }
+
+            continue;
+        }
+
+        if let Some(open) = &mut pause {
+            if let Some(pos) = mouse {
+                open.mouse_move(pos);
+            }
+
+            // Controller buttons bound to Pause only come through as actions
+            match buttons
+                .iter()
+                .find_map(|&(button, state)| open.button(button, state, &settings.bindings))
+                .or_else(|| {
+                    actions
+                        .contains(&(Action::Pause, true))
+                        .then_some(PauseAction::Resume)
+                }) {
+                Some(PauseAction::Resume) => {
+                    game.frozen = false;
+                    pause = None;
+                }
+                Some(PauseAction::Restart) => {
+                    end_attempt(&mut game, &mut progress);
+                    restart(&mut game, &mut progress);
+                    pause = None;
+                }
+                // Switching mid-run starts the level over, so no run is part practice
+                Some(PauseAction::Practice) => {
+                    end_attempt(&mut game, &mut progress);
+                    game.set_practice(!game.practice);
+
+                    if game.tick > 0 {
+                        restart(&mut game, &mut progress);
+                    }
+                }
+                Some(PauseAction::Settings) => settings_menu = Some(SettingsMenu::new()),
+                Some(PauseAction::Edit) => {
+                    match playtested
+                        .take()
+                        .map_or_else(|| Editor::new(&game.level_name), Ok)
+                    {
+                        Ok(open) => {
+                            end_attempt(&mut game, &mut progress);
+                            editor = Some(open);
+                            pause = None;
                         }
+                        Err(error) => eprintln!("{}", error),
                     }
-                    _ => {}
-                },
+                }
+                Some(PauseAction::Quit) => {
+                    end_attempt(&mut game, &mut progress);
+                    menu = Some(Menu::level_select(&game.level_name));
+                    pause = None;
+                }
+                None => {}
+            }
+
+            continue;
+        }
+
+        // The editor takes all input while it is open, Escape playtests its level and Edit on the
+        // pause menu comes back to it
+        if let Some(open) = &mut editor {
+            if let Some(pos) = mouse {
+                open.mouse_move(pos);
+            }
+
+            if let Some(amount) = e.mouse_scroll_args() {
+                open.scroll(amount);
+            }
+
+            if let Some(args) = e.button_args() {
+                if args.button == Button::Keyboard(Key::Escape) && args.state == ButtonState::Press
+                {
+                    game.load_level(&open.lines());
+                    game.playtest = true;
+                    playtested = editor.take();
+                } else {
+                    open.button(args.button, args.state);
+                }
+            }
+
+            continue;
+        }
+
+        if let Some(args) = e.update_args() {
+            if !game.frozen {
+                let finished: bool = game.player.crashed || game.victory;
+
+                game.update(&args);
+
+                if !finished && (game.player.crashed || game.victory) {
+                    end_attempt(&mut game, &mut progress);
+                }
+            }
+
+            // Auto retry starts the next attempt straight away instead of waiting for Pause
+            if settings.auto_retry && game.player.crashed && game.death_time >= settings.retry_delay
+            {
+                retry(&mut game, &mut progress);
+                game.frozen = false;
+            }
+        }
+
+        for (action, pressed) in actions {
+            match action {
+                Action::Jump if !game.frozen => game.player.jumping = pressed,
+                Action::FlipGravity if pressed && !game.frozen && !game.player.crashed => {
+                    game.flip_gravity();
+                }
+                Action::Pause if pressed => {
+                    if game.player.crashed || game.victory {
+                        menu = Some(Menu::level_select(&game.level_name));
+                    } else if game.frozen {
+                        // Levels wait frozen on their first frame until Pause starts them
+                        game.frozen = false;
+                    } else {
+                        game.frozen = true;
+                        pause = Some(PauseMenu::new());
+                    }
+                }
+                // Restarting mid-run ends the attempt where it is, like the pause menu does
+                Action::Restart if pressed && game.tick > 0 => {
+                    if !game.player.crashed && !game.victory {
+                        end_attempt(&mut game, &mut progress);
+                    }
+
+                    retry(&mut game, &mut progress);
+                }
+                Action::PlaceCheckpoint if pressed => game.place_checkpoint(),
+                Action::RemoveCheckpoint if pressed => game.remove_checkpoint(),
                 _ => {}
             }
         }
     }
+
+    // Closing the window mid-run still counts the time and jumps
+    if menu.is_none() && editor.is_none() && !game.player.crashed && !game.victory {
+        end_attempt(&mut game, &mut progress);
+    } else if let Err(error) = progress.save() {
+        eprintln!("failed to save progress: {}", error);
+    }
+}
+
+// Starts a fresh attempt at `level_name` and counts it towards the level's progress
+
+fn start_attempt(
+    game: &mut Game,
+    progress: &mut Progress,
+    level_name: &str,
+) -> Result<(), AssetError> {
+    game.initialize_level(level_name)?;
+    progress.level_mut(level_name).attempts += 1;
+    Ok(())
+}
+
+// Starts the current level over from its file, or from the copy already loaded if the file
+// can't be read anymore. Playtests restart from the editor's copy of the level
+
+fn restart(game: &mut Game, progress: &mut Progress) {
+    if game.playtest {
+        let lines: Vec<String> = game.lines.clone();
+        game.load_level(&lines);
+        return;
+    }
+
+    let level_name: String = game.level_name.clone();
+
+    if let Err(error) = start_attempt(game, progress, &level_name) {
+        eprintln!("{}, replaying the loaded level", error);
+
+        let lines: Vec<String> = game.lines.clone();
+        game.load_level(&lines);
+    }
+}
+
+// After a crash in practice mode, goes back to the last checkpoint instead of the start
+
+fn retry(game: &mut Game, progress: &mut Progress) {
+    if game.practice && game.player.crashed {
+        game.respawn();
+
+        if !game.playtest {
+            progress.level_mut(&game.level_name).attempts += 1;
+        }
+    } else {
+        restart(game, progress);
+    }
+}
+
+// Adds the attempt that just ended, by crashing, finishing or leaving, to the level's totals
+// and saves them. Only crashes and finishes count towards the best percentages, and only from
+// the start of the level
+
+fn end_attempt(game: &mut Game, progress: &mut Progress) {
+    if game.playtest || game.tick == 0 {
+        return;
+    }
+
+    let percent: f32 = game.progress();
+    let finished: bool = game.player.crashed || game.victory;
+    let level: &mut LevelProgress = progress.level_mut(&game.level_name);
+
+    level.jumps += game.player.jumps;
+    level.time += game.attempt_time;
+
+    if finished && game.practice {
+        level.best_practice = level.best_practice.max(percent);
+    } else if finished && !game.from_start_position {
+        game.new_best = percent > level.best;
+        level.best = level.best.max(percent);
+
+        if game.victory && level.completed.is_none() {
+            level.completed = Some(today());
+        }
+    }
+
+    if let Err(error) = progress.save() {
+        eprintln!("failed to save progress: {}", error);
+    }
 }
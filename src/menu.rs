@@ -0,0 +1,391 @@
+use crate::assets::Assets;
+use crate::constants::{BG_COLOR, HEIGHT, LEVELS_DIR, PLAYER_COLOR, WIDTH};
+use crate::game::read_lines;
+use crate::menu_rows::MenuRows;
+use crate::progress::{LevelProgress, Progress};
+use crate::rect::Rect;
+use crate::text::{Align, Text};
+use crate::vector::Vector;
+use graphics::Viewport;
+use opengl_graphics::GlGraphics;
+use piston::{Button, ButtonState, Key, MouseButton};
+use std::fs;
+
+const MAIN_ITEMS: [&str; 3] = ["Play", "Settings", "Quit"];
+const ROW_HEIGHT: f32 = 64.0;
+// Level rows shown at once, the list scrolls to keep the selected one in view
+const VISIBLE_ROWS: usize = 10;
+
+// Name and difficulty come from optional `0,name,...` and `0,difficulty,...` lines in the file
+
+pub struct LevelInfo {
+    pub file: String,
+    pub name: String,
+    pub difficulty: String,
+}
+
+// Levels that can't be read are reported and left out
+
+pub fn scan_levels() -> Vec<LevelInfo> {
+    let Ok(dir) = Assets::new().find_dir(LEVELS_DIR) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<String> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    files.sort();
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let lines: Vec<String> = match read_lines(&dir.join(&file)) {
+                Ok(lines) => lines,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return None;
+                }
+            };
+            let metadata = |key: &str| {
+                lines.iter().find_map(|line| {
+                    let fields: Vec<&str> = line.splitn(3, ',').collect();
+
+                    (fields.len() == 3 && fields[0] == "0" && fields[1] == key)
+                        .then(|| fields[2].to_string())
+                })
+            };
+
+            Some(LevelInfo {
+                name: metadata("name").unwrap_or_else(|| file.clone()),
+                difficulty: metadata("difficulty").unwrap_or_else(|| "unrated".to_string()),
+                file,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Page {
+    Main,
+    LevelSelect,
+}
+
+pub enum MenuAction {
+    Play(String),
+    Settings,
+    Quit,
+}
+
+pub struct Menu {
+    page: Page,
+    selected: usize,
+    scroll: usize,
+    levels: Vec<LevelInfo>,
+    mouse: Vector,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Menu {
+            page: Page::Main,
+            selected: 0,
+            scroll: 0,
+            levels: scan_levels(),
+            mouse: Vector::default(),
+        }
+    }
+
+    // Straight to the level list with `level_name` selected, for leaving a level
+
+    pub fn level_select(level_name: &str) -> Self {
+        let mut menu: Menu = Menu::new();
+
+        menu.page = Page::LevelSelect;
+        menu.select(
+            menu.levels
+                .iter()
+                .position(|level| level.file == level_name)
+                .unwrap_or(0),
+        );
+        menu
+    }
+
+    fn len(&self) -> usize {
+        match self.page {
+            Page::Main => MAIN_ITEMS.len(),
+            Page::LevelSelect => self.levels.len(),
+        }
+    }
+
+    fn select(&mut self, i: usize) {
+        self.selected = i;
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + VISIBLE_ROWS {
+            self.scroll = self.selected + 1 - VISIBLE_ROWS;
+        }
+    }
+
+    fn rows(&self) -> MenuRows {
+        match self.page {
+            Page::Main => MenuRows::new(
+                HEIGHT as f32 / 2.0,
+                ROW_HEIGHT * 1.5,
+                Rect::new(
+                    Vector::new(WIDTH as f32 / 2.0 - 240.0, HEIGHT as f32 / 2.0 - 56.0),
+                    Vector::new(480.0, ROW_HEIGHT * 1.25),
+                ),
+            ),
+            Page::LevelSelect => MenuRows {
+                first: self.scroll,
+                ..MenuRows::new(
+                    320.0,
+                    ROW_HEIGHT,
+                    Rect::new(
+                        Vector::new(200.0, 274.0),
+                        Vector::new(WIDTH as f32 - 400.0, ROW_HEIGHT),
+                    ),
+                )
+            },
+        }
+    }
+
+    fn visible_rows(&self) -> std::ops::Range<usize> {
+        match self.page {
+            Page::Main => 0..MAIN_ITEMS.len(),
+            Page::LevelSelect => self.scroll..self.levels.len().min(self.scroll + VISIBLE_ROWS),
+        }
+    }
+
+    fn activate(&mut self) -> Option<MenuAction> {
+        match (self.page, self.selected) {
+            (Page::Main, 0) => {
+                self.page = Page::LevelSelect;
+                self.select(0);
+                None
+            }
+            (Page::Main, 1) => Some(MenuAction::Settings),
+            (Page::Main, _) => Some(MenuAction::Quit),
+            (Page::LevelSelect, i) => self
+                .levels
+                .get(i)
+                .map(|level| MenuAction::Play(level.file.clone())),
+        }
+    }
+
+    fn back(&mut self) -> Option<MenuAction> {
+        match self.page {
+            Page::Main => Some(MenuAction::Quit),
+            Page::LevelSelect => {
+                self.page = Page::Main;
+                self.selected = 0;
+                None
+            }
+        }
+    }
+
+    // Hovering selects the row under the mouse
+
+    pub fn mouse_move(&mut self, pos: [f64; 2]) {
+        self.mouse = Vector::new(pos[0] as f32, pos[1] as f32);
+
+        if let Some(i) = self.rows().hovered(self.visible_rows(), self.mouse) {
+            self.selected = i;
+        }
+    }
+
+    pub fn scroll(&mut self, amount: [f64; 2]) {
+        if self.page == Page::LevelSelect && !self.levels.is_empty() {
+            let last: usize = self.levels.len().saturating_sub(VISIBLE_ROWS);
+
+            self.scroll = if amount[1] > 0.0 {
+                self.scroll.saturating_sub(1)
+            } else {
+                (self.scroll + 1).min(last)
+            };
+            self.selected = self
+                .selected
+                .clamp(self.scroll, self.scroll + VISIBLE_ROWS - 1);
+        }
+    }
+
+    pub fn button(&mut self, button: Button, state: ButtonState) -> Option<MenuAction> {
+        if state != ButtonState::Press {
+            return None;
+        }
+
+        match button {
+            Button::Keyboard(Key::Up) if self.len() > 0 => {
+                self.select((self.selected + self.len() - 1) % self.len());
+                None
+            }
+            Button::Keyboard(Key::Down) if self.len() > 0 => {
+                self.select((self.selected + 1) % self.len());
+                None
+            }
+            Button::Keyboard(Key::Return | Key::Space) => self.activate(),
+            Button::Keyboard(Key::Escape | Key::Backspace) => self.back(),
+            Button::Mouse(MouseButton::Left) => {
+                if self.rows().contains(self.selected, self.mouse) {
+                    self.activate()
+                } else {
+                    None
+                }
+            }
+            Button::Mouse(MouseButton::Right) => self.back(),
+            _ => None,
+        }
+    }
+
+    pub fn render(
+        &self,
+        gl: &mut GlGraphics,
+        font: &mut Text,
+        viewport: Viewport,
+        progress: &Progress,
+    ) {
+        use graphics::*;
+
+        gl.draw(viewport, |c: Context, gl: &mut GlGraphics| {
+            rectangle(
+                BG_COLOR,
+                [0.0, 0.0, WIDTH as f64, HEIGHT as f64],
+                c.transform,
+                gl,
+            );
+
+            let white: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+            let center: f64 = WIDTH as f64 / 2.0;
+
+            for i in self.visible_rows() {
+                if i == self.selected {
+                    rectangle(
+                        [1.0, 1.0, 1.0, 0.1],
+                        self.rows().row(i).1.to_rectangle(),
+                        c.transform,
+                        gl,
+                    );
+                }
+            }
+
+            match self.page {
+                Page::Main => {
+                    font.render(
+                        PLAYER_COLOR,
+                        "Geometry Dash ",
+                        128,
+                        Align::Center,
+                        c.trans(center, 320.0),
+                        gl,
+                    );
+
+                    for (i, item) in MAIN_ITEMS.iter().enumerate() {
+                        font.render(
+                            if i == self.selected {
+                                PLAYER_COLOR
+                            } else {
+                                white
+                            },
+                            item,
+                            56,
+                            Align::Center,
+                            c.trans(center, self.rows().row(i).0 as f64),
+                            gl,
+                        );
+                    }
+                }
+                Page::LevelSelect => {
+                    font.render(
+                        PLAYER_COLOR,
+                        "Select a level ",
+                        72,
+                        Align::Center,
+                        c.trans(center, 160.0),
+                        gl,
+                    );
+
+                    if self.levels.is_empty() {
+                        font.render(
+                            white,
+                            format!("No levels found in {}/ ", LEVELS_DIR).as_str(),
+                            40,
+                            Align::Center,
+                            c.trans(center, 320.0),
+                            gl,
+                        );
+                    }
+
+                    for (text, x, align) in [
+                        ("Level", 240.0, Align::Left),
+                        ("Difficulty", 860.0, Align::Left),
+                        ("Best", 1280.0, Align::Right),
+                        ("Practice", 1480.0, Align::Right),
+                        ("Attempts", 1680.0, Align::Right),
+                    ] {
+                        font.render([1.0, 1.0, 1.0, 0.6], text, 32, align, c.trans(x, 240.0), gl);
+                    }
+
+                    for i in self.visible_rows() {
+                        let level: &LevelInfo = &self.levels[i];
+                        let record: LevelProgress = progress.get(&level.file);
+                        let y: f64 = self.rows().row(i).0 as f64;
+                        let color: [f32; 4] = if i == self.selected {
+                            PLAYER_COLOR
+                        } else {
+                            white
+                        };
+
+                        font.render(color, &level.name, 40, Align::Left, c.trans(240.0, y), gl);
+                        font.render(
+                            color,
+                            &level.difficulty,
+                            40,
+                            Align::Left,
+                            c.trans(860.0, y),
+                            gl,
+                        );
+                        font.render(
+                            color,
+                            format!("{:.0}%", record.best).as_str(),
+                            40,
+                            Align::Right,
+                            c.trans(1280.0, y),
+                            gl,
+                        );
+                        font.render(
+                            color,
+                            format!("{:.0}%", record.best_practice).as_str(),
+                            40,
+                            Align::Right,
+                            c.trans(1480.0, y),
+                            gl,
+                        );
+                        font.render(
+                            color,
+                            format!("{}", record.attempts).as_str(),
+                            40,
+                            Align::Right,
+                            c.trans(1680.0, y),
+                            gl,
+                        );
+                    }
+
+                    font.render(
+                        [1.0, 1.0, 1.0, 0.6],
+                        "Up / Down or mouse to choose, Enter or click to play, Esc to go back ",
+                        28,
+                        Align::Center,
+                        c.trans(center, HEIGHT as f64 - 60.0),
+                        gl,
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use crate::rect::Rect;
+use crate::vector::Vector;
+use std::ops::Range;
+
+// Evenly spaced rows of a menu list, shared by every screen that lists options. Each row has a
+// text baseline and an area around it that highlights and reacts to the mouse
+
+pub struct MenuRows {
+    pub baseline: f32,
+    pub spacing: f32,
+    pub area: Rect,
+    // Row drawn at the top, for lists scrolled past their start
+    pub first: usize,
+}
+
+impl MenuRows {
+    // `area` surrounds the text of the top row, the rows below it are `spacing` apart
+
+    pub fn new(baseline: f32, spacing: f32, area: Rect) -> Self {
+        MenuRows {
+            baseline,
+            spacing,
+            area,
+            first: 0,
+        }
+    }
+
+    // Baseline of the text on row `i` and the area that reacts to the mouse
+
+    pub fn row(&self, i: usize) -> (f32, Rect) {
+        let offset: f32 = (i as f32 - self.first as f32) * self.spacing;
+
+        (
+            self.baseline + offset,
+            Rect::new(self.area.pos + Vector::new(0.0, offset), self.area.size),
+        )
+    }
+
+    // Row in `rows` under the mouse, which hovering selects
+
+    pub fn hovered(&self, rows: Range<usize>, mouse: Vector) -> Option<usize> {
+        rows.into_iter().find(|&i| self.contains(i, mouse))
+    }
+
+    // Clicks only choose the selected row while the mouse is over it
+
+    pub fn contains(&self, i: usize, mouse: Vector) -> bool {
+        self.row(i).1.contains(mouse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_follow_the_scroll() {
+        let mut rows: MenuRows = MenuRows::new(
+            100.0,
+            50.0,
+            Rect::new(Vector::new(0.0, 60.0), Vector::new(200.0, 50.0)),
+        );
+
+        assert_eq!(rows.row(2).0, 200.0);
+        assert_eq!(rows.hovered(0..5, Vector::new(10.0, 190.0)), Some(2));
+        assert_eq!(rows.hovered(0..2, Vector::new(10.0, 190.0)), None);
+        assert!(!rows.contains(2, Vector::new(300.0, 190.0)));
+
+        rows.first = 2;
+        assert_eq!(rows.row(2).0, 100.0);
+        assert_eq!(rows.hovered(2..7, Vector::new(10.0, 190.0)), Some(4));
+    }
+}
@@ -1,17 +1,109 @@
+use crate::collider::Collider;
+use crate::color::{with_alpha, ColorChannels, CHANNEL_ORB};
+use crate::constants::ORB_HITBOX_SCALE;
+use crate::game_object::{GameObject, Interaction};
+use crate::player::Player;
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::util::parse_nums;
 use crate::vector::Vector;
+use opengl_graphics::GlGraphics;
 
 pub struct Orb {
     pub pos: Vector,
     pub d: f32,
+    pub props: Properties,
     pub activated: bool,
 }
 
 impl Orb {
-    pub fn new(pos: Vector, d: f32) -> Self {
+    pub fn new(pos: Vector, d: f32, props: Properties) -> Self {
         Orb {
             pos,
             d,
+            props,
             activated: false,
         }
     }
+
+    // 3,x,y,d
+
+    pub fn parse(fields: &[&str], props: Properties) -> Self {
+        let nums: Vec<f32> = parse_nums(&fields[1..=3]);
+
+        Orb::new(Vector::new(nums[0], nums[1]), nums[2], props)
+    }
+
+    pub fn hitbox_radius(&self) -> f32 {
+        self.d * self.props.hitbox.unwrap_or(ORB_HITBOX_SCALE) / 2.0
+    }
+}
+
+impl GameObject for Orb {
+    fn name(&self) -> &'static str {
+        "orb"
+    }
+
+    fn props(&self) -> &Properties {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut Properties {
+        &mut self.props
+    }
+
+    fn interaction(&self) -> Interaction {
+        Interaction::Touch
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::Circle {
+            center: self.pos,
+            radius: self.hitbox_radius(),
+        }
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::centered(self.pos, Vector::new(self.d, self.d))
+    }
+
+    fn center(&self) -> Vector {
+        self.pos
+    }
+
+    fn translate(&mut self, delta: Vector) {
+        self.pos += delta;
+    }
+
+    fn rotate(&mut self, pivot: Option<Vector>, degrees: f32) {
+        if let Some(pivot) = pivot {
+            self.pos = self.pos.rotate_about(pivot, degrees);
+        }
+    }
+
+    fn render(&self, colors: &ColorChannels, transform: [[f64; 3]; 2], gl: &mut GlGraphics) {
+        graphics::ellipse(
+            with_alpha(
+                colors.get(self.props.color_or(CHANNEL_ORB)),
+                self.props.alpha,
+            ),
+            [
+                self.pos.x as f64 - (self.d as f64 / 2.0),
+                self.pos.y as f64 - (self.d as f64 / 2.0),
+                self.d as f64,
+                self.d as f64,
+            ],
+            transform,
+            gl,
+        );
+    }
+
+    // Each orb launches the player once, the first tick they touch it while holding jump
+
+    fn on_touch(&mut self, player: &mut Player) {
+        if player.jumping && !self.activated {
+            self.activated = true;
+            player.launch();
+        }
+    }
 }
@@ -0,0 +1,220 @@
+use crate::constants::{HEIGHT, PLAYER_COLOR, WIDTH};
+use crate::input::{input_name, Action, Bindings};
+use crate::menu_rows::MenuRows;
+use crate::rect::Rect;
+use crate::text::{Align, Text};
+use crate::vector::Vector;
+use graphics::Viewport;
+use opengl_graphics::GlGraphics;
+use piston::{Button, ButtonState, Key, MouseButton};
+
+const ITEMS: [PauseAction; 6] = [
+    PauseAction::Resume,
+    PauseAction::Restart,
+    PauseAction::Practice,
+    PauseAction::Settings,
+    PauseAction::Edit,
+    PauseAction::Quit,
+];
+// Letters that choose a row straight away, left out while the letter is bound to Pause
+const SHORTCUTS: [(Key, &str, PauseAction); 5] = [
+    (Key::R, "R restart", PauseAction::Restart),
+    (Key::P, "P practice", PauseAction::Practice),
+    (Key::S, "S settings", PauseAction::Settings),
+    (Key::E, "E edit", PauseAction::Edit),
+    (Key::Q, "Q quit", PauseAction::Quit),
+];
+const ROW_HEIGHT: f32 = 80.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    Practice,
+    Settings,
+    Edit,
+    Quit,
+}
+
+pub struct PauseMenu {
+    selected: usize,
+    mouse: Vector,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        PauseMenu {
+            selected: 0,
+            mouse: Vector::default(),
+        }
+    }
+
+    fn rows() -> MenuRows {
+        MenuRows::new(
+            400.0,
+            ROW_HEIGHT,
+            Rect::new(
+                Vector::new(WIDTH as f32 / 2.0 - 300.0, 344.0),
+                Vector::new(600.0, ROW_HEIGHT),
+            ),
+        )
+    }
+
+    fn label(action: PauseAction, practice: bool) -> &'static str {
+        match action {
+            PauseAction::Resume => "Resume",
+            PauseAction::Restart => "Restart",
+            PauseAction::Practice if practice => "Practice: on",
+            PauseAction::Practice => "Practice: off",
+            PauseAction::Settings => "Settings",
+            PauseAction::Edit => "Edit level",
+            PauseAction::Quit => "Quit to menu",
+        }
+    }
+
+    pub fn mouse_move(&mut self, pos: [f64; 2]) {
+        self.mouse = Vector::new(pos[0] as f32, pos[1] as f32);
+
+        if let Some(i) = PauseMenu::rows().hovered(0..ITEMS.len(), self.mouse) {
+            self.selected = i;
+        }
+    }
+
+    // The inputs bound to Pause resume, and so does Escape, which goes back in every menu and
+    // stands in for the controller's east and start buttons
+
+    fn resumes(button: Button, bindings: &Bindings) -> bool {
+        button == Button::Keyboard(Key::Escape)
+            || input_name(button).is_some_and(|input| bindings.get(Action::Pause).contains(&input))
+    }
+
+    pub fn button(
+        &mut self,
+        button: Button,
+        state: ButtonState,
+        bindings: &Bindings,
+    ) -> Option<PauseAction> {
+        if state != ButtonState::Press {
+            return None;
+        }
+
+        match button {
+            Button::Keyboard(Key::Up) => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+                None
+            }
+            Button::Keyboard(Key::Down) => {
+                self.selected = (self.selected + 1) % ITEMS.len();
+                None
+            }
+            Button::Keyboard(Key::Return | Key::Space) => Some(ITEMS[self.selected]),
+            Button::Mouse(MouseButton::Left)
+                if PauseMenu::rows().contains(self.selected, self.mouse) =>
+            {
+                Some(ITEMS[self.selected])
+            }
+            _ if PauseMenu::resumes(button, bindings) => Some(PauseAction::Resume),
+            Button::Keyboard(key) => SHORTCUTS
+                .iter()
+                .find(|&&(shortcut, _, _)| shortcut == key)
+                .map(|&(_, _, action)| action),
+            _ => None,
+        }
+    }
+
+    // Drawn over the frozen game
+
+    pub fn render(
+        &self,
+        gl: &mut GlGraphics,
+        font: &mut Text,
+        viewport: Viewport,
+        practice: bool,
+        bindings: &Bindings,
+    ) {
+        use graphics::*;
+
+        let hint: String = std::iter::once(format!("{} resume", bindings.describe(Action::Pause)))
+            .chain(
+                SHORTCUTS
+                    .iter()
+                    .filter(|&&(key, _, _)| !PauseMenu::resumes(Button::Keyboard(key), bindings))
+                    .map(|&(_, label, _)| label.to_string()),
+            )
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        gl.draw(viewport, |c: Context, gl: &mut GlGraphics| {
+            let center: f64 = WIDTH as f64 / 2.0;
+
+            rectangle(
+                [0.0, 0.0, 0.0, 0.6],
+                [0.0, 0.0, WIDTH as f64, HEIGHT as f64],
+                c.transform,
+                gl,
+            );
+            font.render(
+                [1.0, 1.0, 1.0, 1.0],
+                "Paused ",
+                96,
+                Align::Center,
+                c.trans(center, 260.0),
+                gl,
+            );
+
+            for (i, &action) in ITEMS.iter().enumerate() {
+                let (y, area): (f32, Rect) = PauseMenu::rows().row(i);
+
+                if i == self.selected {
+                    rectangle([1.0, 1.0, 1.0, 0.1], area.to_rectangle(), c.transform, gl);
+                }
+
+                font.render(
+                    if i == self.selected {
+                        PLAYER_COLOR
+                    } else {
+                        [1.0, 1.0, 1.0, 1.0]
+                    },
+                    PauseMenu::label(action, practice),
+                    48,
+                    Align::Center,
+                    c.trans(center, y as f64),
+                    gl,
+                );
+            }
+
+            font.render(
+                [1.0, 1.0, 1.0, 0.6],
+                &format!("{} ", hint),
+                28,
+                Align::Center,
+                c.trans(center, HEIGHT as f64 - 60.0),
+                gl,
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(bindings: &Bindings, key: Key) -> Option<PauseAction> {
+        PauseMenu::new().button(Button::Keyboard(key), ButtonState::Press, bindings)
+    }
+
+    #[test]
+    fn pause_bindings_resume() {
+        let mut bindings: Bindings = Bindings::default();
+
+        assert!(press(&bindings, Key::Escape) == Some(PauseAction::Resume));
+        assert!(press(&bindings, Key::P) == Some(PauseAction::Practice));
+
+        // A letter bound to Pause resumes instead of acting as its shortcut
+        bindings.set(Action::Pause, vec!["p".to_string()]);
+        assert!(press(&bindings, Key::P) == Some(PauseAction::Resume));
+        assert!(press(&bindings, Key::R) == Some(PauseAction::Restart));
+        // Escape still goes back, like in every other menu
+        assert!(press(&bindings, Key::Escape) == Some(PauseAction::Resume));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::assets::Assets;
+use crate::config::Config;
+use crate::constants::{GRAVITY, JUMP_ARC, JUMP_VELOCITY, PHYSICS_FILE, PLAYER_SIZE, PLAYER_SPEED};
+use std::str::FromStr;
+
+const KEYS: [&str; 5] = ["gravity", "speed", "size", "jump_velocity", "jump_arc"];
+
+// How the player moves. The built-in values are the game's original feel, profiles in
+// PHYSICS_FILE and per-level overrides change them without recompiling
+
+pub struct Physics {
+    pub gravity: f32,
+    pub speed: f32,
+    pub size: u32,
+    pub jump_velocity: f32,
+    pub jump_arc: f32,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            gravity: GRAVITY,
+            speed: PLAYER_SPEED,
+            size: PLAYER_SIZE,
+            jump_velocity: JUMP_VELOCITY,
+            jump_arc: JUMP_ARC,
+        }
+    }
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Physics {
+            ..Default::default()
+        }
+    }
+
+    // The physics file from the assets. Read once and reused for every level load, so keys no
+    // profile understands are reported here rather than on every restart
+
+    pub fn config() -> Option<Config> {
+        let config: Config = Assets::new()
+            .find(PHYSICS_FILE)
+            .ok()
+            .and_then(|path| Config::load(&path))?;
+
+        for (name, key) in config
+            .sections
+            .iter()
+            .flat_map(|(name, pairs)| pairs.iter().map(move |(key, _)| (name, key)))
+            .filter(|(_, key)| !KEYS.contains(&key.as_str()))
+        {
+            eprintln!("unknown physics value {} in profile {}", key, name);
+        }
+
+        Some(config)
+    }
+
+    // The built-in values overridden by the `[name]` section of the physics file. Only the
+    // default profile may be missing, any other unknown profile is reported and left at the
+    // built-in values
+
+    pub fn from_config(config: Option<&Config>, name: &str) -> Self {
+        let mut physics: Physics = Physics::new();
+
+        match config {
+            Some(config) if config.has_section(name) => {
+                physics.apply(|key| config.get(name, key));
+            }
+            _ if name == "default" => {}
+            _ => eprintln!(
+                "unknown physics profile {}, using the built-in values",
+                name
+            ),
+        }
+
+        physics
+    }
+
+    // Overrides every value `get` has a replacement for. Values that don't parse are reported
+    // and keep what they were
+
+    pub fn apply<'a>(&mut self, get: impl Fn(&str) -> Option<&'a str>) {
+        set(&get, "gravity", &mut self.gravity);
+        set(&get, "speed", &mut self.speed);
+        set(&get, "size", &mut self.size);
+        set(&get, "jump_velocity", &mut self.jump_velocity);
+        set(&get, "jump_arc", &mut self.jump_arc);
+    }
+}
+
+fn set<'a, T: FromStr>(get: &impl Fn(&str) -> Option<&'a str>, key: &str, value: &mut T) {
+    let Some(x) = get(key) else {
+        return;
+    };
+
+    match x.parse::<T>() {
+        Ok(x) => *value = x,
+        Err(_) => eprintln!("invalid physics value {} = {}", key, x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_profile_uses_the_built_in_values() {
+        let config: Config = Config::parse("[floaty]\ngravity = 20\n");
+        let physics: Physics = Physics::from_config(Some(&config), "missing");
+
+        assert_eq!(physics.gravity, GRAVITY);
+        assert_eq!(physics.speed, PLAYER_SPEED);
+    }
+
+    #[test]
+    fn bad_values_keep_the_built_in_values() {
+        let config: Config =
+            Config::parse("[typo]\ngravity = 20x\nsped = 100\nsize = -5\njump_arc = 300\n");
+        let physics: Physics = Physics::from_config(Some(&config), "typo");
+
+        assert_eq!(physics.gravity, GRAVITY);
+        assert_eq!(physics.speed, PLAYER_SPEED);
+        assert_eq!(physics.size, PLAYER_SIZE);
+        assert_eq!(physics.jump_arc, 300.0);
+    }
+
+    #[test]
+    fn bad_override_keeps_the_profile_value() {
+        let config: Config = Config::parse("[floaty]\ngravity = 20\n");
+        let mut physics: Physics = Physics::from_config(Some(&config), "floaty");
+
+        physics.apply(|key| match key {
+            "gravity" => Some("heavy"),
+            "speed" => Some("700"),
+            _ => None,
+        });
+
+        assert_eq!(physics.gravity, 20.0);
+        assert_eq!(physics.speed, 700.0);
+    }
+}
@@ -1,9 +1,13 @@
-use crate::block::Block;
-use crate::constants::{GRAVITY, GROUND_Y_FLIP, GROUND_Y_NORMAL, PLAYER_SIZE, PLAYER_SPEED, WIDTH};
-use crate::orb::Orb;
-use crate::spike::Spike;
+use crate::constants::{
+    GRAVITY, GROUND_Y_NORMAL, JUMP_ARC, JUMP_VELOCITY, PLAYER_SCREEN_X, PLAYER_SIZE,
+    PLAYER_SOLID_SCALE, PLAYER_SPEED,
+};
+use crate::physics::Physics;
+use crate::rect::Rect;
+use crate::section::Section;
+use crate::side::Side;
+use crate::start_position::StartPosition;
 use crate::surface_result::SurfaceResult;
-use crate::util::*;
 use crate::vector::Vector;
 
 pub struct Player {
@@ -17,6 +21,9 @@ pub struct Player {
     pub vel: Vector,
     pub acc: Vector,
     pub jump: Vector,
+    pub jump_velocity: f32,
+    pub jump_arc: f32,
+    pub jumps: u32,
 }
 
 impl Default for Player {
@@ -29,61 +36,68 @@ impl Default for Player {
             jumping: false,
             crashed: false,
             pos: Vector::new(
-                WIDTH as f32 * 0.2,
+                PLAYER_SCREEN_X,
                 GROUND_Y_NORMAL - (PLAYER_SIZE as f32 / 2.0),
             ),
             vel: Vector::new(PLAYER_SPEED, 0.0),
             acc: Vector::new(0.0, GRAVITY),
             jump: Vector::new(f32::MAX, f32::MAX),
+            jump_velocity: JUMP_VELOCITY,
+            jump_arc: JUMP_ARC,
+            jumps: 0,
         }
     }
 }
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(physics: &Physics) -> Self {
         Player {
+            size: physics.size,
+            pos: Vector::new(
+                PLAYER_SCREEN_X,
+                GROUND_Y_NORMAL - (physics.size as f32 / 2.0),
+            ),
+            vel: Vector::new(physics.speed, 0.0),
+            acc: Vector::new(0.0, physics.gravity),
+            jump_velocity: physics.jump_velocity,
+            jump_arc: physics.jump_arc,
             ..Default::default()
         }
     }
 
-    pub fn on_ground(&self) -> SurfaceResult {
-        let mut result: bool = false;
-        let mut y: f32 = 0.0;
+    // Spawns mid-level in the air, the first tick lands it on whatever is below
 
-        if self.pos.y + (self.size / 2) as f32 >= GROUND_Y_NORMAL && !self.gravity_flip {
-            result = true;
-            y = GROUND_Y_NORMAL - (self.size / 2) as f32;
-        } else if self.pos.y - (self.size / 2) as f32 <= GROUND_Y_FLIP && self.gravity_flip {
-            result = true;
-            y = GROUND_Y_FLIP + (self.size / 2) as f32;
-        }
+    pub fn at_start(physics: &Physics, start: &StartPosition) -> Self {
+        let mut player: Player = Player {
+            size: (physics.size as f32 * start.size).round() as u32,
+            pos: start.pos,
+            grounded: false,
+            ..Player::new(physics)
+        };
 
-        if result {
-            SurfaceResult::OnSurface(y)
-        } else {
-            SurfaceResult::NotOnSurface
+        player.vel.x *= start.speed;
+
+        if start.gravity_flip {
+            player.gravity_flip = true;
+            player.acc.y = -player.acc.y;
         }
+
+        player
     }
 
-    pub fn on_block(&self, block: &Block) -> SurfaceResult {
+    pub fn on_ground(&self, section: &Section) -> SurfaceResult {
         let mut result: bool = false;
         let mut y: f32 = 0.0;
 
-        if self.pos.x + (self.size as f32 / 2.0) > block.pos.x
-            && self.pos.x - (self.size as f32 / 2.0) < block.pos.x + block.size.x
-        {
-            if self.gravity_flip {
-                if self.pos.y - (self.size as f32 / 2.0) < block.pos.y + block.size.y
-                    && self.pos.y + (self.size as f32 / 2.0) > block.pos.y + block.size.y
-                {
-                    result = true;
-                    y = block.pos.y + block.size.y + (self.size as f32 / 2.0);
-                }
-            } else if self.pos.y - (self.size as f32 / 2.0) < block.pos.y
-                && self.pos.y + (self.size as f32 / 2.0) > block.pos.y
-            {
+        if let (Some(floor), false) = (section.floor, self.gravity_flip) {
+            if self.pos.y + (self.size / 2) as f32 >= floor {
                 result = true;
-                y = block.pos.y - (self.size as f32 / 2.0);
+                y = floor - (self.size / 2) as f32;
+            }
+        } else if let (Some(ceiling), true) = (section.ceiling, self.gravity_flip) {
+            if self.pos.y - (self.size / 2) as f32 <= ceiling {
+                result = true;
+                y = ceiling + (self.size / 2) as f32;
             }
         }
 
@@ -94,73 +108,150 @@ impl Player {
         }
     }
 
-    pub fn check_block_crash(&mut self, block: &Block) {
-        self.crashed = self.pos.x + (self.size as f32 / 2.0) >= block.pos.x
-            && self.pos.x - (self.size as f32 / 2.0) <= block.pos.x + block.size.x
-            && ((self.gravity_flip
-                && self.pos.y + (self.size as f32 / 2.0) >= block.pos.y
-                && self.pos.y - (self.size as f32 / 2.0) <= block.pos.y)
-                || (!self.gravity_flip
-                    && self.pos.y - (self.size as f32 / 2.0) <= block.pos.y + block.size.y
-                    && self.pos.y + (self.size as f32 / 2.0) >= block.pos.y + block.size.y));
+    pub fn flip_gravity(&mut self, section: &Section) {
+        self.gravity_flip = !self.gravity_flip;
+        self.acc.y = -self.acc.y;
+        self.grounded = false;
+        self.jump.y = if self.gravity_flip {
+            section.ceiling.unwrap_or(self.pos.y)
+        } else {
+            section.floor.unwrap_or(self.pos.y)
+        }
     }
 
-    pub fn check_spike_crash(&mut self, spike: &Spike) {
-        let mut n: usize;
-        let mut i: usize = 0;
+    pub fn on_rect(&self, rect: &Rect) -> SurfaceResult {
+        let hitbox: Rect = self.hitbox();
+        let half: f32 = self.size as f32 / 2.0;
 
-        while i < spike.vertices.len() {
-            n = i + 1;
-            if n == spike.vertices.len() {
-                n = 0;
-            }
+        if hitbox.max().x <= rect.pos.x || hitbox.pos.x >= rect.max().x {
+            return SurfaceResult::NotOnSurface;
+        }
 
-            let vc: [f64; 2] = spike.vertices[i];
-            let vn: [f64; 2] = spike.vertices[n];
-
-            let collision: bool = line_rect(
-                &Vector::new(vc[0] as f32, vc[1] as f32),
-                &Vector::new(vn[0] as f32, vn[1] as f32),
-                &Vector::new(
-                    self.pos.x - (self.size as f32 / 2.0),
-                    self.pos.y - (self.size as f32 / 2.0),
-                ),
-                &Vector::new(self.size as f32, self.size as f32),
-            );
-            let inside: bool = polygon_point(
-                spike.vertices,
-                Vector::new(
-                    self.pos.x - (self.size as f32 / 2.0),
-                    self.pos.y - (self.size as f32 / 2.0),
-                ),
-            );
-
-            self.crashed = collision || inside;
-
-            i += 1;
+        if self.gravity_flip {
+            if hitbox.pos.y < rect.max().y && hitbox.max().y > rect.max().y {
+                return SurfaceResult::OnSurface(rect.max().y + half);
+            }
+        } else if hitbox.pos.y < rect.pos.y && hitbox.max().y > rect.pos.y {
+            return SurfaceResult::OnSurface(rect.pos.y - half);
         }
+
+        SurfaceResult::NotOnSurface
     }
 
-    pub fn check_orb_collide(&self, orb: &Orb) -> bool {
-        let mut tx: f32 = orb.pos.x;
-        let mut ty: f32 = orb.pos.y;
+    pub fn standing_on(&self, rect: &Rect) -> bool {
+        let hitbox: Rect = self.hitbox();
 
-        if orb.pos.x < self.pos.x - (self.size as f32 / 2.0) {
-            tx = self.pos.x - (self.size as f32 / 2.0);
-        } else if orb.pos.x > self.pos.x + (self.size as f32 / 2.0) {
-            tx = self.pos.x + (self.size as f32 / 2.0);
-        }
+        hitbox.max().x > rect.pos.x
+            && hitbox.pos.x < rect.max().x
+            && if self.gravity_flip {
+                (hitbox.pos.y - rect.max().y).abs() < 1.0
+            } else {
+                (hitbox.max().y - rect.pos.y).abs() < 1.0
+            }
+    }
+
+    pub fn land(&mut self, y: f32) {
+        self.angle = 0.0;
+        self.grounded = true;
+        self.jump = Vector::new(f32::MAX, f32::MAX);
+        self.vel.y = 0.0;
+        self.pos.y = y;
+    }
 
-        if orb.pos.y < self.pos.y - (self.size as f32 / 2.0) {
-            ty = self.pos.y - (self.size as f32 / 2.0);
-        } else if orb.pos.y > self.pos.y + (self.size as f32 / 2.0) {
-            ty = self.pos.y + (self.size as f32 / 2.0);
+    // Starts a jump from the current position, used by both the ground and orbs
+
+    pub fn launch(&mut self) {
+        self.jumps += 1;
+        self.jump = Vector::new(self.pos.x + self.jump_arc, self.pos.y);
+        self.grounded = false;
+        self.vel.y = if self.gravity_flip {
+            self.jump_velocity
+        } else {
+            -self.jump_velocity
+        };
+    }
+
+    // The full box decides landings, spikes and orbs. Block deaths only happen once the smaller
+    // solid box in the middle of the player overlaps the block, like GD's inner hitbox
+
+    pub fn hitbox(&self) -> Rect {
+        Rect::centered(self.pos, Vector::new(self.size as f32, self.size as f32))
+    }
+
+    pub fn solid_hitbox(&self) -> Rect {
+        let half: f32 = self.solid_half();
+
+        Rect::centered(self.pos, Vector::new(half * 2.0, half * 2.0))
+    }
+
+    pub fn solid_half(&self) -> f32 {
+        self.size as f32 * PLAYER_SOLID_SCALE / 2.0
+    }
+
+    // Positions between `from` and the current position, never further apart than half the
+    // player's size, so consecutive hitboxes overlap and nothing thin can slip between them
+
+    pub fn sweep_samples(&self, from: Vector) -> Vec<Vector> {
+        let steps: usize = (self.pos.dist(from) / (self.size as f32 / 2.0))
+            .ceil()
+            .max(1.0) as usize;
+
+        (1..=steps)
+            .map(|step| from.lerp(self.pos, step as f32 / steps as f32))
+            .collect()
+    }
+
+    // Time of impact of a box of half size `half` centered on the player, moving from `from` to
+    // the current position, against an axis-aligned rectangle, and the face it hits first.
+    // Touching or already overlapping at the start doesn't count, the discrete checks handle
+    // resting contact
+
+    pub fn sweep_rect(&self, from: Vector, half: f32, rect: &Rect) -> Option<(f32, Side)> {
+        // Growing the rectangle by the box's half size reduces this to a moving point
+        let grown: Rect = rect.expand(Vector::new(half, half));
+        let min: Vector = grown.pos;
+        let max: Vector = grown.max();
+        let d: Vector = self.pos - from;
+
+        let (x_entry, x_exit): (f32, f32) = slab(from.x, d.x, min.x, max.x)?;
+        let (y_entry, y_exit): (f32, f32) = slab(from.y, d.y, min.y, max.y)?;
+
+        let entry: f32 = x_entry.max(y_entry);
+        let exit: f32 = x_exit.min(y_exit);
+
+        if entry >= exit || !(0.0..=1.0).contains(&entry) {
+            return None;
         }
 
-        let dx: f32 = orb.pos.x - tx;
-        let dy: f32 = orb.pos.y - ty;
-        let d: f32 = (dx.powi(2) + dy.powi(2)).sqrt();
+        let side: Side = if x_entry > y_entry {
+            if d.x > 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            }
+        } else if d.y > 0.0 {
+            Side::Top
+        } else {
+            Side::Bottom
+        };
+
+        Some((entry, side))
+    }
+}
+
+// Entry and exit times of a point moving along one axis through the open interval (min, max)
+
+fn slab(start: f32, d: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if d == 0.0 {
+        if start > min && start < max {
+            Some((f32::MIN, f32::MAX))
+        } else {
+            None
+        }
+    } else {
+        let t1: f32 = (min - start) / d;
+        let t2: f32 = (max - start) / d;
 
-        d <= orb.d / 2.0
+        Some((t1.min(t2), t1.max(t2)))
     }
 }
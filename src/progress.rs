@@ -0,0 +1,156 @@
+use crate::assets::data_dir;
+use crate::config::Config;
+use crate::constants::PROGRESS_FILE;
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Bump when the file layout changes, and convert older files in `Progress::load`
+const SAVE_VERSION: u32 = 1;
+
+#[derive(Clone, Default)]
+pub struct LevelProgress {
+    pub attempts: u32,
+    pub jumps: u32,
+    // Furthest the player got, as a percentage of the level
+    pub best: f32,
+    pub best_practice: f32,
+    // Date of the first normal mode completion, as YYYY-MM-DD
+    pub completed: Option<String>,
+    // Seconds played
+    pub time: f32,
+}
+
+// What the player has achieved on each level, keyed by level file name. Kept in PROGRESS_FILE
+// in the user data directory
+
+#[derive(Default)]
+pub struct Progress {
+    pub levels: HashMap<String, LevelProgress>,
+    path: Option<PathBuf>,
+    // Set when the file comes from a newer version, so it isn't overwritten with less data
+    read_only: bool,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        let mut progress: Progress = Progress {
+            path: data_dir().map(|dir| dir.join(PROGRESS_FILE)),
+            ..Default::default()
+        };
+
+        let Some(config) = progress.path.as_deref().and_then(Config::load) else {
+            return progress;
+        };
+
+        let version: u32 = config
+            .get("", "version")
+            .and_then(|x| x.parse::<u32>().ok())
+            .unwrap_or(SAVE_VERSION);
+
+        if version > SAVE_VERSION {
+            eprintln!(
+                "progress file is from a newer version ({}), it won't be saved to",
+                version
+            );
+            progress.read_only = true;
+        }
+
+        for (name, _) in config.sections.iter().filter(|(name, _)| !name.is_empty()) {
+            let number = |key: &str| {
+                config
+                    .get(name, key)
+                    .and_then(|x| x.parse::<f32>().ok())
+                    .unwrap_or(0.0)
+            };
+
+            progress.levels.insert(
+                name.clone(),
+                LevelProgress {
+                    attempts: number("attempts") as u32,
+                    jumps: number("jumps") as u32,
+                    best: number("best"),
+                    best_practice: number("best_practice"),
+                    completed: config.get(name, "completed").map(|x| x.to_string()),
+                    time: number("time"),
+                },
+            );
+        }
+
+        progress
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(());
+        };
+
+        if self.read_only {
+            return Ok(());
+        }
+
+        let mut names: Vec<&String> = self.levels.keys().collect();
+        names.sort();
+
+        let mut config: Config = Config {
+            sections: vec![(
+                String::new(),
+                vec![("version".to_string(), SAVE_VERSION.to_string())],
+            )],
+        };
+
+        for name in names {
+            let level: &LevelProgress = &self.levels[name];
+            let mut pairs: Vec<(String, String)> = vec![
+                ("attempts".to_string(), level.attempts.to_string()),
+                ("jumps".to_string(), level.jumps.to_string()),
+                ("best".to_string(), level.best.to_string()),
+                ("best_practice".to_string(), level.best_practice.to_string()),
+                ("time".to_string(), level.time.to_string()),
+            ];
+
+            if let Some(date) = &level.completed {
+                pairs.push(("completed".to_string(), date.clone()));
+            }
+
+            config.sections.push((name.clone(), pairs));
+        }
+
+        config.save(path)
+    }
+
+    pub fn get(&self, level_name: &str) -> LevelProgress {
+        self.levels.get(level_name).cloned().unwrap_or_default()
+    }
+
+    pub fn level_mut(&mut self, level_name: &str) -> &mut LevelProgress {
+        self.levels.entry(level_name.to_string()).or_default()
+    }
+}
+
+// Today's date in UTC as YYYY-MM-DD
+
+pub fn today() -> String {
+    let days: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64 / 86400);
+
+    // Days since 1970-01-01 to a civil date, counting in 400 year eras that start in March
+    let z: i64 = days + 719468;
+    let era: i64 = z.div_euclid(146097);
+    let day_of_era: i64 = z - era * 146097;
+    let year_of_era: i64 =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month: i64 = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,52 @@
+use crate::util::field_property;
+
+// Optional per-object settings shared by every object kind in a level file
+
+#[derive(PartialEq)]
+pub struct Properties {
+    pub line: usize,
+    pub group: u32,
+    pub color: Option<String>,
+    pub enabled: bool,
+    pub alpha: f32,
+    pub ghost: bool,
+    pub hitbox: Option<f32>,
+}
+
+impl Properties {
+    // `line` is the 1-based line in the level file, used to point back at the object
+
+    pub fn parse(fields: &[&str], line: usize) -> Self {
+        Properties {
+            line,
+            group: field_property(fields, "group")
+                .map(|x| x.parse::<u32>().unwrap())
+                .unwrap_or(0),
+            color: field_property(fields, "color").map(|x| x.to_string()),
+            enabled: field_property(fields, "enabled")
+                .map(|x| x.parse::<bool>().unwrap())
+                .unwrap_or(true),
+            alpha: field_property(fields, "alpha")
+                .map(|x| x.parse::<f32>().unwrap())
+                .unwrap_or(1.0),
+            ghost: field_property(fields, "ghost")
+                .map(|x| x.parse::<bool>().unwrap())
+                .unwrap_or(false),
+            hitbox: field_property(fields, "hitbox").map(|x| x.parse::<f32>().unwrap()),
+        }
+    }
+
+    // Ghost objects lose their hitbox once an alpha trigger fades them out completely
+
+    pub fn solid(&self) -> bool {
+        self.enabled && !(self.ghost && self.alpha <= 0.0)
+    }
+
+    pub fn visible(&self) -> bool {
+        self.enabled && self.alpha > 0.0
+    }
+
+    pub fn color_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.color.as_deref().unwrap_or(default)
+    }
+}
@@ -0,0 +1,194 @@
+use crate::segment::Segment;
+use crate::vector::Vector;
+
+// Axis-aligned rectangle with `pos` at the top left corner
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub pos: Vector,
+    pub size: Vector,
+}
+
+impl Rect {
+    pub fn new(pos: Vector, size: Vector) -> Self {
+        Rect { pos, size }
+    }
+
+    pub fn centered(center: Vector, size: Vector) -> Self {
+        Rect::new(center - size / 2.0, size)
+    }
+
+    // Smallest rectangle containing every point, or an empty one at the origin without points
+
+    pub fn around(points: impl IntoIterator<Item = Vector>) -> Self {
+        let mut points = points.into_iter();
+
+        let Some(first) = points.next() else {
+            return Rect::default();
+        };
+
+        let (min, max): (Vector, Vector) = points.fold((first, first), |(min, max), point| {
+            (
+                Vector::new(min.x.min(point.x), min.y.min(point.y)),
+                Vector::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        });
+
+        Rect::new(min, max - min)
+    }
+
+    pub fn max(&self) -> Vector {
+        self.pos + self.size
+    }
+
+    pub fn center(&self) -> Vector {
+        self.pos + self.size / 2.0
+    }
+
+    pub fn corners(&self) -> [Vector; 4] {
+        let max: Vector = self.max();
+
+        [
+            self.pos,
+            Vector::new(max.x, self.pos.y),
+            max,
+            Vector::new(self.pos.x, max.y),
+        ]
+    }
+
+    // Top, right, bottom and left, in that order
+
+    pub fn edges(&self) -> [Segment; 4] {
+        let [a, b, c, d]: [Vector; 4] = self.corners();
+
+        [
+            Segment::new(a, b),
+            Segment::new(b, c),
+            Segment::new(c, d),
+            Segment::new(d, a),
+        ]
+    }
+
+    // Strict, so rectangles that only share an edge don't overlap
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        let max: Vector = self.max();
+        let other_max: Vector = other.max();
+
+        max.x > other.pos.x
+            && self.pos.x < other_max.x
+            && max.y > other.pos.y
+            && self.pos.y < other_max.y
+    }
+
+    pub fn contains(&self, point: Vector) -> bool {
+        let max: Vector = self.max();
+
+        point.x > self.pos.x && point.x < max.x && point.y > self.pos.y && point.y < max.y
+    }
+
+    // Closest point inside or on the rectangle
+
+    pub fn clamp(&self, point: Vector) -> Vector {
+        let max: Vector = self.max();
+
+        Vector::new(
+            point.x.clamp(self.pos.x, max.x),
+            point.y.clamp(self.pos.y, max.y),
+        )
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::around([self.pos, self.max(), other.pos, other.max()])
+    }
+
+    // [x, y, w, h] as the graphics crate expects
+
+    pub fn to_rectangle(self) -> [f64; 4] {
+        [
+            self.pos.x as f64,
+            self.pos.y as f64,
+            self.size.x as f64,
+            self.size.y as f64,
+        ]
+    }
+
+    // Grown by `amount` on every side
+
+    pub fn expand(&self, amount: Vector) -> Rect {
+        Rect::new(self.pos - amount, self.size + amount * 2.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+        Rect::new(Vector::new(x, y), Vector::new(w, h))
+    }
+
+    #[test]
+    fn intersects_is_strict() {
+        let a: Rect = rect(0.0, 0.0, 10.0, 10.0);
+
+        assert!(a.intersects(&rect(5.0, 5.0, 10.0, 10.0)));
+        assert!(a.intersects(&rect(2.0, 2.0, 1.0, 1.0)));
+        // Sharing an edge or a corner isn't overlapping
+        assert!(!a.intersects(&rect(10.0, 0.0, 10.0, 10.0)));
+        assert!(!a.intersects(&rect(0.0, 10.0, 10.0, 10.0)));
+        assert!(!a.intersects(&rect(10.0, 10.0, 5.0, 5.0)));
+        assert!(!a.intersects(&rect(20.0, 0.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn contains_is_strict() {
+        let a: Rect = rect(0.0, 0.0, 10.0, 10.0);
+
+        assert!(a.contains(Vector::new(5.0, 5.0)));
+        assert!(!a.contains(Vector::new(0.0, 5.0)));
+        assert!(!a.contains(Vector::new(10.0, 10.0)));
+        assert!(!a.contains(Vector::new(11.0, 5.0)));
+    }
+
+    #[test]
+    fn around() {
+        assert_eq!(Rect::around([]), Rect::default());
+        assert_eq!(
+            Rect::around([Vector::new(3.0, 4.0)]),
+            rect(3.0, 4.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            Rect::around([
+                Vector::new(5.0, -2.0),
+                Vector::new(-1.0, 3.0),
+                Vector::new(2.0, 8.0),
+            ]),
+            rect(-1.0, -2.0, 6.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn expand() {
+        assert_eq!(
+            rect(10.0, 10.0, 20.0, 30.0).expand(Vector::new(5.0, 1.0)),
+            rect(5.0, 9.0, 30.0, 32.0)
+        );
+        assert_eq!(
+            rect(10.0, 10.0, 20.0, 30.0).expand(Vector::new(-5.0, 0.0)),
+            rect(15.0, 10.0, 10.0, 30.0)
+        );
+    }
+
+    #[test]
+    fn union_and_clamp() {
+        let a: Rect = rect(0.0, 0.0, 10.0, 10.0);
+
+        assert_eq!(
+            a.union(&rect(20.0, -5.0, 5.0, 5.0)),
+            rect(0.0, -5.0, 25.0, 15.0)
+        );
+        assert_eq!(a.clamp(Vector::new(-3.0, 4.0)), Vector::new(0.0, 4.0));
+        assert_eq!(a.clamp(Vector::new(5.0, 5.0)), Vector::new(5.0, 5.0));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::constants::{HEIGHT, WIDTH};
+use graphics::Viewport;
+
+// Where the view lands in the window. Everything is drawn in world units on a WIDTH x HEIGHT
+// view, which is scaled to fit the window while keeping its shape. The bars left over on two
+// sides stay black
+
+#[derive(Clone, Copy)]
+pub struct Screen {
+    // Window size in points, which mouse positions are in
+    pub window_size: [f64; 2],
+    // Window size in pixels, larger than the points on high DPI displays
+    pub draw_size: [u32; 2],
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen {
+            window_size: [WIDTH as f64, HEIGHT as f64],
+            draw_size: [WIDTH, HEIGHT],
+        }
+    }
+}
+
+impl Screen {
+    pub fn new(window_size: [f64; 2], draw_size: [u32; 2]) -> Self {
+        Screen {
+            window_size,
+            draw_size,
+        }
+    }
+
+    // Window points per world unit
+
+    pub fn scale(&self) -> f64 {
+        (self.window_size[0] / WIDTH as f64).min(self.window_size[1] / HEIGHT as f64)
+    }
+
+    // Top left corner of the view in window points
+
+    fn offset(&self) -> [f64; 2] {
+        [
+            (self.window_size[0] - WIDTH as f64 * self.scale()) / 2.0,
+            (self.window_size[1] - HEIGHT as f64 * self.scale()) / 2.0,
+        ]
+    }
+
+    // Viewport of the view. Its window size is picked so the context's transform takes world
+    // units, whatever the real size of the window is
+
+    pub fn viewport(&self) -> Viewport {
+        let pixels: f64 = self.draw_size[0] as f64 / self.window_size[0];
+        let offset: [f64; 2] = self.offset();
+        let rect: [i32; 4] = [
+            (offset[0] * pixels).round() as i32,
+            (offset[1] * pixels).round() as i32,
+            (WIDTH as f64 * self.scale() * pixels).round().max(1.0) as i32,
+            (HEIGHT as f64 * self.scale() * pixels).round().max(1.0) as i32,
+        ];
+
+        Viewport {
+            rect,
+            draw_size: self.draw_size,
+            window_size: [
+                self.draw_size[0] as f64 * WIDTH as f64 / rect[2] as f64,
+                self.draw_size[1] as f64 * HEIGHT as f64 / rect[3] as f64,
+            ],
+        }
+    }
+
+    // The whole window, for clearing the bars
+
+    pub fn window_viewport(&self) -> Viewport {
+        Viewport {
+            rect: [0, 0, self.draw_size[0] as i32, self.draw_size[1] as i32],
+            draw_size: self.draw_size,
+            window_size: self.window_size,
+        }
+    }
+
+    // A mouse position in window points to world units on the view
+
+    pub fn view_position(&self, pos: [f64; 2]) -> [f64; 2] {
+        let offset: [f64; 2] = self.offset();
+
+        [
+            (pos[0] - offset[0]) / self.scale(),
+            (pos[1] - offset[1]) / self.scale(),
+        ]
+    }
+}
@@ -0,0 +1,53 @@
+use crate::constants::{GROUND_Y_FLIP, GROUND_Y_NORMAL};
+use crate::properties::Properties;
+
+// Floor and ceiling heights from `x` onwards; `None` leaves that side open for tall levels
+
+pub struct Section {
+    pub x: f32,
+    pub floor: Option<f32>,
+    pub ceiling: Option<f32>,
+    // Level file line it came from, 0 for the default section
+    pub line: usize,
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        Section {
+            x: f32::MIN,
+            floor: Some(GROUND_Y_NORMAL),
+            ceiling: Some(GROUND_Y_FLIP),
+            line: 0,
+        }
+    }
+}
+
+impl Section {
+    pub fn new(x: f32, floor: Option<f32>, ceiling: Option<f32>, line: usize) -> Self {
+        Section {
+            x,
+            floor,
+            ceiling,
+            line,
+        }
+    }
+
+    // 16,x,floor,ceiling where either height may be `none`
+
+    pub fn parse(fields: &[&str], props: &Properties) -> Self {
+        Section::new(
+            fields[1].parse::<f32>().unwrap(),
+            fields[2].parse::<f32>().ok(),
+            fields[3].parse::<f32>().ok(),
+            props.line,
+        )
+    }
+}
+
+// `sections` is kept sorted by x and always starts with the default section
+
+pub fn section_at(sections: &[Section], x: f32) -> &Section {
+    let index: usize = sections.partition_point(|section| section.x <= x);
+
+    &sections[index.saturating_sub(1)]
+}
@@ -0,0 +1,78 @@
+use crate::rect::Rect;
+use crate::vector::Vector;
+
+// Line segment between two points
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub a: Vector,
+    pub b: Vector,
+}
+
+impl Segment {
+    pub fn new(a: Vector, b: Vector) -> Self {
+        Segment { a, b }
+    }
+
+    pub fn direction(&self) -> Vector {
+        self.b - self.a
+    }
+
+    // http://jeffreythompson.org/collision-detection/line-line.php
+    // Parallel segments never intersect, even when they overlap
+
+    pub fn intersects(&self, other: &Segment) -> bool {
+        let d: Vector = self.direction();
+        let other_d: Vector = other.direction();
+        let offset: Vector = self.a - other.a;
+        let denominator: f32 = d.cross(other_d);
+
+        let ua: f32 = other_d.cross(offset) / denominator;
+        let ub: f32 = d.cross(offset) / denominator;
+
+        (0.0..=1.0).contains(&ua) && (0.0..=1.0).contains(&ub)
+    }
+
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        rect.edges().iter().any(|edge| self.intersects(edge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(ax: f32, ay: f32, bx: f32, by: f32) -> Segment {
+        Segment::new(Vector::new(ax, ay), Vector::new(bx, by))
+    }
+
+    #[test]
+    fn crossing_segments_intersect() {
+        assert!(segment(0.0, 0.0, 10.0, 10.0).intersects(&segment(0.0, 10.0, 10.0, 0.0)));
+        assert!(!segment(0.0, 0.0, 4.0, 4.0).intersects(&segment(0.0, 10.0, 10.0, 0.0)));
+    }
+
+    #[test]
+    fn touching_segments_intersect() {
+        // End to end, and an end resting on the middle of the other
+        assert!(segment(0.0, 0.0, 5.0, 5.0).intersects(&segment(5.0, 5.0, 10.0, 0.0)));
+        assert!(segment(5.0, 0.0, 5.0, 5.0).intersects(&segment(0.0, 5.0, 10.0, 5.0)));
+    }
+
+    #[test]
+    fn parallel_segments_never_intersect() {
+        assert!(!segment(0.0, 0.0, 10.0, 0.0).intersects(&segment(0.0, 5.0, 10.0, 5.0)));
+        // Not even when they overlap along the same line
+        assert!(!segment(0.0, 0.0, 10.0, 0.0).intersects(&segment(5.0, 0.0, 15.0, 0.0)));
+    }
+
+    #[test]
+    fn intersects_rect_edges() {
+        let rect: Rect = Rect::new(Vector::new(0.0, 0.0), Vector::new(10.0, 10.0));
+
+        assert!(segment(-5.0, 5.0, 5.0, 5.0).intersects_rect(&rect));
+        // Fully inside crosses no edge
+        assert!(!segment(2.0, 2.0, 8.0, 8.0).intersects_rect(&rect));
+        assert!(!segment(-5.0, -5.0, -1.0, 20.0).intersects_rect(&rect));
+    }
+}
@@ -0,0 +1,139 @@
+use crate::assets::data_dir;
+use crate::config::Config;
+use crate::constants::{FPS, HEIGHT, RETRY_DELAY, SETTINGS_FILE, WIDTH};
+use crate::input::Bindings;
+use std::io;
+use std::path::PathBuf;
+
+// Player preferences, kept in SETTINGS_FILE in the user data directory. Missing or invalid
+// values fall back to the defaults
+
+pub struct Settings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub fps_cap: u64,
+    // Multisampling samples per pixel, 0 turns it off
+    pub msaa: u8,
+    pub show_fps: bool,
+    pub auto_retry: bool,
+    // Seconds between crashing and the automatic retry
+    pub retry_delay: f32,
+    pub show_progress_bar: bool,
+    pub bindings: Bindings,
+    path: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            width: WIDTH,
+            height: HEIGHT,
+            fullscreen: false,
+            vsync: true,
+            fps_cap: FPS,
+            msaa: 4,
+            show_fps: true,
+            auto_retry: false,
+            retry_delay: RETRY_DELAY,
+            show_progress_bar: true,
+            bindings: Bindings::new(),
+            path: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let mut settings: Settings = Settings {
+            path: data_dir().map(|dir| dir.join(SETTINGS_FILE)),
+            ..Default::default()
+        };
+
+        let Some(config) = settings.path.as_deref().and_then(Config::load) else {
+            return settings;
+        };
+
+        let flag = |section: &str, key: &str, default: bool| {
+            config
+                .get(section, key)
+                .and_then(|x| x.parse::<bool>().ok())
+                .unwrap_or(default)
+        };
+
+        if let Some((width, height)) = config
+            .get("display", "resolution")
+            .and_then(|x| x.split_once('x'))
+            .and_then(|(w, h)| Some((w.trim().parse::<u32>().ok()?, h.trim().parse::<u32>().ok()?)))
+        {
+            settings.width = width;
+            settings.height = height;
+        }
+
+        settings.fullscreen = flag("display", "fullscreen", settings.fullscreen);
+        settings.vsync = flag("display", "vsync", settings.vsync);
+        settings.fps_cap = config
+            .get("display", "fps_cap")
+            .and_then(|x| x.parse::<u64>().ok())
+            .filter(|&x| x > 0)
+            .unwrap_or(settings.fps_cap);
+        settings.msaa = config
+            .get("display", "msaa")
+            .and_then(|x| x.parse::<u8>().ok())
+            .unwrap_or(settings.msaa);
+        settings.show_fps = flag("display", "show_fps", settings.show_fps);
+        settings.auto_retry = flag("gameplay", "auto_retry", settings.auto_retry);
+        settings.retry_delay = config
+            .get("gameplay", "retry_delay")
+            .and_then(|x| x.parse::<f32>().ok())
+            .filter(|&x| x >= 0.0)
+            .unwrap_or(settings.retry_delay);
+        settings.show_progress_bar =
+            flag("gameplay", "show_progress_bar", settings.show_progress_bar);
+        settings.bindings = Bindings::parse(
+            &config
+                .sections
+                .iter()
+                .filter(|(name, _)| name == "bindings")
+                .flat_map(|(_, pairs)| pairs.iter().cloned())
+                .collect::<Vec<(String, String)>>(),
+        );
+
+        settings
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(());
+        };
+
+        let pair = |key: &str, value: String| (key.to_string(), value);
+        let config: Config = Config {
+            sections: vec![
+                (
+                    "display".to_string(),
+                    vec![
+                        pair("resolution", format!("{}x{}", self.width, self.height)),
+                        pair("fullscreen", self.fullscreen.to_string()),
+                        pair("vsync", self.vsync.to_string()),
+                        pair("fps_cap", self.fps_cap.to_string()),
+                        pair("msaa", self.msaa.to_string()),
+                        pair("show_fps", self.show_fps.to_string()),
+                    ],
+                ),
+                (
+                    "gameplay".to_string(),
+                    vec![
+                        pair("auto_retry", self.auto_retry.to_string()),
+                        pair("retry_delay", self.retry_delay.to_string()),
+                        pair("show_progress_bar", self.show_progress_bar.to_string()),
+                    ],
+                ),
+                ("bindings".to_string(), self.bindings.pairs()),
+            ],
+        };
+
+        config.save(path)
+    }
+}
@@ -0,0 +1,342 @@
+use crate::constants::{HEIGHT, PLAYER_COLOR, WIDTH};
+use crate::input::{input_name, Action};
+use crate::menu_rows::MenuRows;
+use crate::rect::Rect;
+use crate::settings::Settings;
+use crate::text::{Align, Text};
+use crate::vector::Vector;
+use graphics::Viewport;
+use opengl_graphics::GlGraphics;
+use piston::{Button, ButtonState, Key, MouseButton};
+
+const ITEMS: [Item; 16] = [
+    Item::Resolution,
+    Item::Fullscreen,
+    Item::Vsync,
+    Item::FpsCap,
+    Item::Msaa,
+    Item::ShowFps,
+    Item::AutoRetry,
+    Item::RetryDelay,
+    Item::ProgressBar,
+    Item::Binding(Action::Jump),
+    Item::Binding(Action::FlipGravity),
+    Item::Binding(Action::Pause),
+    Item::Binding(Action::Restart),
+    Item::Binding(Action::PlaceCheckpoint),
+    Item::Binding(Action::RemoveCheckpoint),
+    Item::Back,
+];
+const RESOLUTIONS: [(u32, u32); 6] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+const FPS_CAPS: [u64; 5] = [30, 60, 120, 144, 240];
+const MSAA_SAMPLES: [u8; 4] = [0, 2, 4, 8];
+const RETRY_DELAYS: [f32; 6] = [0.5, 0.75, 1.0, 1.5, 2.0, 3.0];
+const ROW_HEIGHT: f32 = 46.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    FpsCap,
+    Msaa,
+    ShowFps,
+    AutoRetry,
+    RetryDelay,
+    ProgressBar,
+    Binding(Action),
+    Back,
+}
+
+// The option after or before `current` in `options`, wrapping around. Values set by hand in the
+// settings file that aren't in the list start over from the first option
+
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, forward: bool) -> T {
+    let i: usize = match options.iter().position(|&x| x == current) {
+        Some(i) if forward => (i + 1) % options.len(),
+        Some(i) => (i + options.len() - 1) % options.len(),
+        None => 0,
+    };
+
+    options[i]
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+// Opened from the main menu and the pause menu. Changes apply as they are made, except the ones
+// the window is built with, which apply on the next start. A new resolution resizes the window
+// once the screen closes
+
+pub struct SettingsMenu {
+    selected: usize,
+    mouse: Vector,
+    // Set after choosing a binding row, the next input pressed is added to or removed from it
+    waiting: Option<Action>,
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        SettingsMenu {
+            selected: 0,
+            mouse: Vector::default(),
+            waiting: None,
+        }
+    }
+
+    fn rows() -> MenuRows {
+        MenuRows::new(
+            230.0,
+            ROW_HEIGHT,
+            Rect::new(
+                Vector::new(WIDTH as f32 / 2.0 - 500.0, 196.0),
+                Vector::new(1000.0, ROW_HEIGHT),
+            ),
+        )
+    }
+
+    fn label(item: Item) -> &'static str {
+        match item {
+            Item::Resolution => "Resolution",
+            Item::Fullscreen => "Fullscreen *",
+            Item::Vsync => "VSync *",
+            Item::FpsCap => "FPS cap",
+            Item::Msaa => "Anti-aliasing *",
+            Item::ShowFps => "Show FPS",
+            Item::AutoRetry => "Auto retry",
+            Item::RetryDelay => "Auto retry delay",
+            Item::ProgressBar => "Progress bar",
+            Item::Binding(action) => action.label(),
+            Item::Back => "Back",
+        }
+    }
+
+    fn value(item: Item, settings: &Settings) -> String {
+        match item {
+            Item::Resolution => format!("{}x{}", settings.width, settings.height),
+            Item::Fullscreen => on_off(settings.fullscreen).to_string(),
+            Item::Vsync => on_off(settings.vsync).to_string(),
+            Item::FpsCap => settings.fps_cap.to_string(),
+            Item::Msaa if settings.msaa == 0 => "off".to_string(),
+            Item::Msaa => format!("{}x", settings.msaa),
+            Item::ShowFps => on_off(settings.show_fps).to_string(),
+            Item::AutoRetry => on_off(settings.auto_retry).to_string(),
+            Item::RetryDelay => format!("{} s", settings.retry_delay),
+            Item::ProgressBar => on_off(settings.show_progress_bar).to_string(),
+            Item::Binding(action) => settings.bindings.get(action).join(", "),
+            Item::Back => String::new(),
+        }
+    }
+
+    fn change(item: Item, settings: &mut Settings, forward: bool) {
+        match item {
+            Item::Resolution => {
+                (settings.width, settings.height) =
+                    cycle(&RESOLUTIONS, (settings.width, settings.height), forward);
+            }
+            Item::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Item::Vsync => settings.vsync = !settings.vsync,
+            Item::FpsCap => settings.fps_cap = cycle(&FPS_CAPS, settings.fps_cap, forward),
+            Item::Msaa => settings.msaa = cycle(&MSAA_SAMPLES, settings.msaa, forward),
+            Item::ShowFps => settings.show_fps = !settings.show_fps,
+            Item::AutoRetry => settings.auto_retry = !settings.auto_retry,
+            Item::RetryDelay => {
+                settings.retry_delay = cycle(&RETRY_DELAYS, settings.retry_delay, forward);
+            }
+            Item::ProgressBar => settings.show_progress_bar = !settings.show_progress_bar,
+            Item::Binding(_) | Item::Back => {}
+        }
+    }
+
+    pub fn mouse_move(&mut self, pos: [f64; 2]) {
+        self.mouse = Vector::new(pos[0] as f32, pos[1] as f32);
+
+        if let Some(i) = SettingsMenu::rows().hovered(0..ITEMS.len(), self.mouse) {
+            self.selected = i;
+        }
+    }
+
+    // Adds `input` to or removes it from the binding row waiting for one, if there is one, and
+    // returns whether there was. Controller buttons come through here too
+
+    pub fn capture(&mut self, input: &str, settings: &mut Settings) -> bool {
+        let Some(action) = self.waiting.take() else {
+            return false;
+        };
+
+        settings.bindings.toggle(action, input);
+        true
+    }
+
+    // Returns true once the screen should close
+
+    pub fn button(&mut self, button: Button, state: ButtonState, settings: &mut Settings) -> bool {
+        if state != ButtonState::Press {
+            return false;
+        }
+
+        // Any input can be bound, Escape and the mouse buttons included
+        if self.waiting.is_some() {
+            if let Some(input) = input_name(button) {
+                self.capture(&input, settings);
+            }
+
+            return false;
+        }
+
+        let item: Item = ITEMS[self.selected];
+
+        match (button, item) {
+            (Button::Keyboard(Key::Return | Key::Space), Item::Binding(action)) => {
+                self.waiting = Some(action);
+                false
+            }
+            (Button::Mouse(MouseButton::Left), Item::Binding(action))
+                if SettingsMenu::rows().contains(self.selected, self.mouse) =>
+            {
+                self.waiting = Some(action);
+                false
+            }
+            (Button::Keyboard(Key::Delete), Item::Binding(action)) => {
+                settings.bindings.reset(action);
+                false
+            }
+            (Button::Keyboard(Key::Up), _) => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len();
+                false
+            }
+            (Button::Keyboard(Key::Down), _) => {
+                self.selected = (self.selected + 1) % ITEMS.len();
+                false
+            }
+            (Button::Keyboard(Key::Left), _) => {
+                SettingsMenu::change(item, settings, false);
+                false
+            }
+            (Button::Keyboard(Key::Right), _) => {
+                SettingsMenu::change(item, settings, true);
+                false
+            }
+            (Button::Keyboard(Key::Escape | Key::Backspace), _) => true,
+            (Button::Keyboard(Key::Return | Key::Space), _) => {
+                SettingsMenu::change(item, settings, true);
+                item == Item::Back
+            }
+            (Button::Mouse(MouseButton::Left), _)
+                if SettingsMenu::rows().contains(self.selected, self.mouse) =>
+            {
+                SettingsMenu::change(item, settings, true);
+                item == Item::Back
+            }
+            (Button::Mouse(MouseButton::Right), _) => true,
+            _ => false,
+        }
+    }
+
+    // Drawn over whichever screen opened it
+
+    pub fn render(
+        &self,
+        gl: &mut GlGraphics,
+        font: &mut Text,
+        viewport: Viewport,
+        settings: &Settings,
+    ) {
+        use graphics::*;
+
+        gl.draw(viewport, |c: Context, gl: &mut GlGraphics| {
+            let center: f64 = WIDTH as f64 / 2.0;
+
+            rectangle(
+                [0.0, 0.0, 0.0, 0.85],
+                [0.0, 0.0, WIDTH as f64, HEIGHT as f64],
+                c.transform,
+                gl,
+            );
+            font.render(
+                PLAYER_COLOR,
+                "Settings ",
+                72,
+                Align::Center,
+                c.trans(center, 140.0),
+                gl,
+            );
+
+            for (i, &item) in ITEMS.iter().enumerate() {
+                let (y, area): (f32, Rect) = SettingsMenu::rows().row(i);
+                let color: [f32; 4] = if i == self.selected {
+                    PLAYER_COLOR
+                } else {
+                    [1.0, 1.0, 1.0, 1.0]
+                };
+
+                if i == self.selected {
+                    rectangle([1.0, 1.0, 1.0, 0.1], area.to_rectangle(), c.transform, gl);
+                }
+
+                if item == Item::Back {
+                    font.render(
+                        color,
+                        SettingsMenu::label(item),
+                        32,
+                        Align::Center,
+                        c.trans(center, y as f64),
+                        gl,
+                    );
+                    continue;
+                }
+
+                font.render(
+                    color,
+                    SettingsMenu::label(item),
+                    32,
+                    Align::Left,
+                    c.trans(center - 460.0, y as f64),
+                    gl,
+                );
+                font.render(
+                    color,
+                    &match (self.waiting, item) {
+                        (Some(waiting), Item::Binding(action)) if waiting == action => {
+                            "press an input to add or remove ".to_string()
+                        }
+                        _ => SettingsMenu::value(item, settings),
+                    },
+                    32,
+                    Align::Right,
+                    c.trans(center + 460.0, y as f64),
+                    gl,
+                );
+            }
+
+            font.render(
+                [1.0, 1.0, 1.0, 0.6],
+                "* applies the next time the game starts. Delete resets a control to its defaults ",
+                28,
+                Align::Center,
+                c.trans(center, HEIGHT as f64 - 100.0),
+                gl,
+            );
+            font.render(
+                [1.0, 1.0, 1.0, 0.6],
+                "Up / Down to choose, Left / Right or click to change, Esc to go back ",
+                28,
+                Align::Center,
+                c.trans(center, HEIGHT as f64 - 60.0),
+                gl,
+            );
+        });
+    }
+}
@@ -0,0 +1,7 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+// Uniform grid of columns along the level's x axis. Each object is listed in every column its
+// horizontal extent touches, so a query only visits the objects in the columns it overlaps and
+// costs the same no matter how long the level is
+
+pub struct SpatialIndex {
+    cell_width: f32,
+    cells: HashMap<i32, Vec<usize>>,
+    spans: Vec<(i32, i32)>,
+}
+
+impl SpatialIndex {
+    pub fn new(cell_width: f32) -> Self {
+        SpatialIndex {
+            cell_width,
+            cells: HashMap::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.spans.clear();
+    }
+
+    // Ids are handed out in insertion order, so they match indices into the object list
+
+    pub fn insert(&mut self, min_x: f32, max_x: f32) -> usize {
+        let id: usize = self.spans.len();
+        let span: (i32, i32) = self.span(min_x, max_x);
+
+        for cell in span.0..=span.1 {
+            self.cells.entry(cell).or_default().push(id);
+        }
+
+        self.spans.push(span);
+        id
+    }
+
+    pub fn update(&mut self, id: usize, min_x: f32, max_x: f32) {
+        let old: (i32, i32) = self.spans[id];
+        let new: (i32, i32) = self.span(min_x, max_x);
+
+        if old == new {
+            return;
+        }
+
+        for cell in old.0..=old.1 {
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                ids.retain(|&other| other != id);
+            }
+        }
+
+        for cell in new.0..=new.1 {
+            self.cells.entry(cell).or_default().push(id);
+        }
+
+        self.spans[id] = new;
+    }
+
+    // Ids of every object whose column range overlaps [min_x, max_x], sorted and without
+    // duplicates so callers see objects in level file order
+
+    pub fn query(&self, min_x: f32, max_x: f32) -> Vec<usize> {
+        let (first, last): (i32, i32) = self.span(min_x, max_x);
+        let mut ids: Vec<usize> = (first..=last)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn span(&self, min_x: f32, max_x: f32) -> (i32, i32) {
+        (
+            (min_x / self.cell_width).floor() as i32,
+            (max_x / self.cell_width).floor() as i32,
+        )
+    }
+}
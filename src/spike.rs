@@ -1,36 +1,185 @@
+use crate::collider::{vertex_bounds, Collider};
+use crate::color::{with_alpha, ColorChannels, CHANNEL_SPIKE};
+use crate::constants::SPIKE_HITBOX_SCALE;
+use crate::crash_report::Contact;
+use crate::game_object::{GameObject, Interaction};
+use crate::player::Player;
+use crate::properties::Properties;
+use crate::rect::Rect;
+use crate::segment::Segment;
+use crate::util::parse_nums;
 use crate::vector::Vector;
+use opengl_graphics::GlGraphics;
 
 pub struct Spike {
     pub pos: Vector,
     pub size: Vector,
     pub flip: bool,
-    pub height: f64,
+    pub angle: f32,
+    pub props: Properties,
     pub vertices: [[f64; 2]; 3],
 }
 
 impl Spike {
-    pub fn new(pos: Vector, size: Vector, flip: bool) -> Self {
-        Spike {
+    pub fn new(pos: Vector, size: Vector, flip: bool, props: Properties) -> Self {
+        let mut spike: Spike = Spike {
             flip,
-            height: if flip {
-                (pos.y + size.y) as f64
-            } else {
-                (pos.y - size.y) as f64
-            },
-            vertices: [
-                [(pos.x - (size.x / 2.0)) as f64, pos.y as f64],
-                [(pos.x + (size.x / 2.0)) as f64, pos.y as f64],
-                [
-                    pos.x as f64,
-                    if flip {
-                        (pos.y - size.y) as f64
-                    } else {
-                        (pos.y + size.y) as f64
-                    },
-                ],
-            ],
+            angle: 0.0,
+            props,
+            vertices: [[0.0; 2]; 3],
             pos,
             size,
+        };
+
+        spike.update_vertices();
+        spike
+    }
+
+    // 2,x,y,w,h,flip
+
+    pub fn parse(fields: &[&str], props: Properties) -> Self {
+        let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+        Spike::new(
+            Vector::new(nums[0], nums[1]),
+            Vector::new(nums[2], nums[3]),
+            fields[5].parse::<bool>().unwrap(),
+            props,
+        )
+    }
+
+    // Vertices rotate about `pos`, the middle of the spike's base
+
+    pub fn update_vertices(&mut self) {
+        let corners: [Vector; 3] = [
+            Vector::new(self.pos.x - (self.size.x / 2.0), self.pos.y),
+            Vector::new(self.pos.x + (self.size.x / 2.0), self.pos.y),
+            Vector::new(
+                self.pos.x,
+                if self.flip {
+                    self.pos.y - self.size.y
+                } else {
+                    self.pos.y + self.size.y
+                },
+            ),
+        ];
+
+        self.vertices = corners.map(|corner| corner.rotate_about(self.pos, self.angle).to_vertex());
+    }
+
+    // Spikes are traditionally much smaller to hit than they look, so the hitbox is the drawn
+    // triangle scaled down around its centroid
+
+    pub fn hitbox(&self) -> [[f64; 2]; 3] {
+        let scale: f64 = self.props.hitbox.unwrap_or(SPIKE_HITBOX_SCALE) as f64;
+        let cx: f64 = self.vertices.iter().map(|v| v[0]).sum::<f64>() / 3.0;
+        let cy: f64 = self.vertices.iter().map(|v| v[1]).sum::<f64>() / 3.0;
+
+        self.vertices
+            .map(|v| [cx + (v[0] - cx) * scale, cy + (v[1] - cy) * scale])
+    }
+}
+
+impl GameObject for Spike {
+    fn name(&self) -> &'static str {
+        "spike"
+    }
+
+    fn props(&self) -> &Properties {
+        &self.props
+    }
+
+    fn props_mut(&mut self) -> &mut Properties {
+        &mut self.props
+    }
+
+    fn interaction(&self) -> Interaction {
+        Interaction::Hazard
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::Triangle(self.hitbox())
+    }
+
+    fn bounds(&self) -> Rect {
+        vertex_bounds(&self.vertices)
+    }
+
+    // Spikes pivot around the middle of their base rather than their centroid
+
+    fn center(&self) -> Vector {
+        self.pos
+    }
+
+    fn translate(&mut self, delta: Vector) {
+        self.pos += delta;
+        self.update_vertices();
+    }
+
+    fn rotate(&mut self, pivot: Option<Vector>, degrees: f32) {
+        if let Some(pivot) = pivot {
+            self.pos = self.pos.rotate_about(pivot, degrees);
         }
+
+        self.angle += degrees;
+        self.update_vertices();
+    }
+
+    fn render(&self, colors: &ColorChannels, transform: [[f64; 3]; 2], gl: &mut GlGraphics) {
+        graphics::polygon(
+            with_alpha(
+                colors.get(self.props.color_or(CHANNEL_SPIKE)),
+                self.props.alpha,
+            ),
+            &self.vertices,
+            transform,
+            gl,
+        );
+    }
+
+    // First hitbox edge crossing the player's box, if any
+
+    fn contact(&self, player: &Player) -> Contact {
+        let hitbox: Rect = player.hitbox();
+        let vertices: [Vector; 3] = self.hitbox().map(Vector::from_vertex);
+
+        (0..vertices.len())
+            .find(|&i| {
+                Segment::new(vertices[i], vertices[(i + 1) % vertices.len()])
+                    .intersects_rect(&hitbox)
+            })
+            .map_or(Contact::Inside, Contact::SpikeEdge)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::polygon_rect;
+
+    fn spike(fields: &[&str]) -> Spike {
+        Spike::new(
+            Vector::new(100.0, 100.0),
+            Vector::new(50.0, 50.0),
+            false,
+            Properties::parse(fields, 1),
+        )
+    }
+
+    #[test]
+    fn hitbox_property_scales_the_triangle() {
+        // Covers the left end of the drawn base
+        let corner: Rect = Rect::new(Vector::new(70.0, 95.0), Vector::new(10.0, 10.0));
+        let full: Spike = spike(&["hitbox=1"]);
+        let default: Spike = spike(&[]);
+
+        assert_eq!(full.hitbox(), full.vertices);
+        assert!(polygon_rect(&full.hitbox(), &corner));
+        assert!(!polygon_rect(&default.hitbox(), &corner));
+
+        // The base shrinks to SPIKE_HITBOX_SCALE of the drawn width
+        let width: f64 = default.hitbox()[1][0] - default.hitbox()[0][0];
+
+        assert!((width - 50.0 * SPIKE_HITBOX_SCALE as f64).abs() < 1e-6);
     }
 }
@@ -0,0 +1,41 @@
+use crate::vector::Vector;
+
+// Where attempts begin instead of the start of the level, so testers can drill a late part
+// without replaying everything before it. `speed` and `size` scale the physics profile
+
+pub struct StartPosition {
+    pub pos: Vector,
+    pub gravity_flip: bool,
+    pub speed: f32,
+    pub size: f32,
+}
+
+impl StartPosition {
+    pub fn new(pos: Vector, gravity_flip: bool, speed: f32, size: f32) -> Self {
+        StartPosition {
+            pos,
+            gravity_flip,
+            speed,
+            size,
+        }
+    }
+
+    // 17,x,y,mode,gravity,speed,size. The cube is the only mode so far, any other is reported and
+    // played as a cube
+
+    pub fn parse(fields: &[&str]) -> Self {
+        if fields[3] != "cube" {
+            eprintln!("unknown player mode {}, using cube", fields[3]);
+        }
+
+        StartPosition::new(
+            Vector::new(
+                fields[1].parse::<f32>().unwrap(),
+                fields[2].parse::<f32>().unwrap(),
+            ),
+            fields[4] == "flip",
+            fields[5].parse::<f32>().unwrap(),
+            fields[6].parse::<f32>().unwrap(),
+        )
+    }
+}
@@ -0,0 +1,328 @@
+use crate::assets::AssetError;
+use crate::rect::Rect;
+use crate::vector::Vector;
+use freetype::{face::LoadFlag, Bitmap, Face, GlyphSlot, Library};
+use graphics::{Context, Image, Transformed};
+use opengl_graphics::{GlGraphics, Texture, TextureSettings};
+use std::collections::HashMap;
+use std::path::Path;
+
+const EMBEDDED_FONT: &[u8] = include_bytes!("../fonts/DejaVuSansMono.ttf");
+const ATLAS_WIDTH: u32 = 1024;
+// Empty pixels between glyphs so linear filtering never bleeds a neighbour in
+const ATLAS_PADDING: u32 = 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GlyphMetrics {
+    pub advance: f32,
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+// Width of `text` along the baseline
+
+pub fn text_width(text: &str, mut metrics: impl FnMut(char) -> GlyphMetrics) -> f32 {
+    text.chars().map(|ch| metrics(ch).advance).sum()
+}
+
+// Where every glyph's bitmap goes for `text` whose baseline starts, is centered or ends at
+// (x, y) depending on `align`. Metrics come from a closure so layout works without a font or GPU
+
+pub fn layout(
+    text: &str,
+    x: f32,
+    y: f32,
+    align: Align,
+    mut metrics: impl FnMut(char) -> GlyphMetrics,
+) -> Vec<(char, Rect)> {
+    let glyphs: Vec<(char, GlyphMetrics)> = text.chars().map(|ch| (ch, metrics(ch))).collect();
+    let width: f32 = glyphs.iter().map(|(_, glyph)| glyph.advance).sum();
+    let mut pen: f32 = match align {
+        Align::Left => x,
+        Align::Center => x - (width / 2.0),
+        Align::Right => x - width,
+    };
+
+    glyphs
+        .into_iter()
+        .map(|(ch, glyph)| {
+            let rect: Rect = Rect::new(
+                Vector::new(pen + glyph.left, y - glyph.top),
+                Vector::new(glyph.width, glyph.height),
+            );
+
+            pen += glyph.advance;
+            (ch, rect)
+        })
+        .collect()
+}
+
+struct Glyph {
+    metrics: GlyphMetrics,
+    source: [f64; 4],
+}
+
+// Every glyph used so far at one pixel size, packed into rows of a single alpha texture
+
+struct Atlas {
+    pixels: Vec<u8>,
+    height: u32,
+    cursor: [u32; 2],
+    row_height: u32,
+    glyphs: HashMap<char, Glyph>,
+    // Dropped whenever a glyph is added and uploaded again on the next draw
+    texture: Option<Texture>,
+}
+
+impl Atlas {
+    fn new() -> Self {
+        Atlas {
+            pixels: vec![0; (ATLAS_WIDTH * 64) as usize],
+            height: 64,
+            cursor: [0, 0],
+            row_height: 0,
+            glyphs: HashMap::new(),
+            texture: None,
+        }
+    }
+
+    // Copies a glyph bitmap into the next free spot, starting a new row or doubling the height
+    // when it doesn't fit, and returns where it went
+
+    fn insert(&mut self, bitmap: &Bitmap) -> [f64; 4] {
+        let width: u32 = bitmap.width() as u32;
+        let rows: u32 = bitmap.rows() as u32;
+        let pitch: usize = bitmap.pitch().unsigned_abs() as usize;
+
+        if self.cursor[0] + width + ATLAS_PADDING > ATLAS_WIDTH {
+            self.cursor = [0, self.cursor[1] + self.row_height];
+            self.row_height = 0;
+        }
+
+        while self.cursor[1] + rows + ATLAS_PADDING > self.height {
+            self.height *= 2;
+            self.pixels.resize((ATLAS_WIDTH * self.height) as usize, 0);
+        }
+
+        for row in 0..rows as usize {
+            let from: usize = row * pitch;
+            let to: usize =
+                (self.cursor[1] as usize + row) * ATLAS_WIDTH as usize + self.cursor[0] as usize;
+
+            self.pixels[to..to + width as usize]
+                .copy_from_slice(&bitmap.buffer()[from..from + width as usize]);
+        }
+
+        let source: [f64; 4] = [
+            self.cursor[0] as f64,
+            self.cursor[1] as f64,
+            width as f64,
+            rows as f64,
+        ];
+
+        self.cursor[0] += width + ATLAS_PADDING;
+        self.row_height = self.row_height.max(rows + ATLAS_PADDING);
+        self.texture = None;
+
+        source
+    }
+}
+
+// Loads the font once and rasterizes each glyph once per size
+
+pub struct Text {
+    face: Face,
+    atlases: HashMap<u32, Atlas>,
+}
+
+impl Text {
+    pub fn load(path: &Path) -> Result<Self, AssetError> {
+        let error = |message: String| AssetError::Load {
+            path: path.to_path_buf(),
+            message,
+        };
+        let library: Library = Library::init().map_err(|e| error(e.to_string()))?;
+        let face: Face = library
+            .new_face(path, 0)
+            .map_err(|e| error(e.to_string()))?;
+
+        Ok(Text::from_face(face))
+    }
+
+    // Font compiled into the binary, for when the asset one can't be found
+
+    pub fn embedded() -> Self {
+        let library: Library = Library::init().unwrap();
+
+        Text::from_face(library.new_memory_face(EMBEDDED_FONT.to_vec(), 0).unwrap())
+    }
+
+    fn from_face(face: Face) -> Self {
+        Text {
+            face,
+            atlases: HashMap::new(),
+        }
+    }
+
+    fn metrics(&mut self, ch: char, size: u32) -> GlyphMetrics {
+        let atlas: &mut Atlas = self.atlases.entry(size).or_insert_with(Atlas::new);
+
+        if let Some(glyph) = atlas.glyphs.get(&ch) {
+            return glyph.metrics;
+        }
+
+        self.face.set_pixel_sizes(0, size).unwrap();
+        self.face.load_char(ch as usize, LoadFlag::RENDER).unwrap();
+
+        let slot: &GlyphSlot = self.face.glyph();
+        let bitmap: Bitmap = slot.bitmap();
+        let metrics: GlyphMetrics = GlyphMetrics {
+            advance: (slot.advance().x >> 6) as f32,
+            left: slot.bitmap_left() as f32,
+            top: slot.bitmap_top() as f32,
+            width: bitmap.width() as f32,
+            height: bitmap.rows() as f32,
+        };
+        let source: [f64; 4] = atlas.insert(&bitmap);
+
+        atlas.glyphs.insert(ch, Glyph { metrics, source });
+        metrics
+    }
+
+    pub fn width(&mut self, text: &str, size: u32) -> f32 {
+        text_width(text, |ch| self.metrics(ch, size))
+    }
+
+    // Draws with the baseline at the origin of `c`, which starts, centers or ends the line
+    // depending on `align`
+
+    pub fn render(
+        &mut self,
+        color: [f32; 4],
+        text: &str,
+        size: u32,
+        align: Align,
+        c: Context,
+        gl: &mut GlGraphics,
+    ) {
+        // Glyphs are rasterized at the size they end up on screen and scaled back, so text stays
+        // sharp in a scaled window. The scale is rounded to eighths so resizing the window only
+        // ever makes a few atlases
+        let raster: u32 = c.viewport.map_or(size, |viewport| {
+            let pixels: f64 =
+                c.transform[0][0].hypot(c.transform[1][0]) * viewport.rect[2] as f64 / 2.0;
+
+            (size as f64 * (pixels * 8.0).round() / 8.0)
+                .round()
+                .max(1.0) as u32
+        });
+        let c: Context = c.scale(size as f64 / raster as f64, size as f64 / raster as f64);
+        let placed: Vec<(char, Rect)> =
+            layout(text, 0.0, 0.0, align, |ch| self.metrics(ch, raster));
+        let atlas: &mut Atlas = self.atlases.entry(raster).or_insert_with(Atlas::new);
+
+        if atlas.texture.is_none() {
+            atlas.texture = Some(
+                Texture::from_memory_alpha(
+                    &atlas.pixels,
+                    ATLAS_WIDTH,
+                    atlas.height,
+                    &TextureSettings::new(),
+                )
+                .unwrap(),
+            );
+        }
+
+        let texture: &Texture = atlas.texture.as_ref().unwrap();
+
+        for (ch, rect) in placed {
+            Image::new_color(color)
+                .src_rect(atlas.glyphs[&ch].source)
+                .draw(
+                    texture,
+                    &c.draw_state,
+                    c.transform.trans(rect.pos.x as f64, rect.pos.y as f64),
+                    gl,
+                );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Narrow "i", a "g" that hangs below the baseline and 10 wide everything else
+
+    fn metrics(ch: char) -> GlyphMetrics {
+        match ch {
+            'i' => GlyphMetrics {
+                advance: 4.0,
+                left: 1.0,
+                top: 12.0,
+                width: 2.0,
+                height: 12.0,
+            },
+            'g' => GlyphMetrics {
+                advance: 10.0,
+                left: 1.0,
+                top: 8.0,
+                width: 8.0,
+                height: 12.0,
+            },
+            ' ' => GlyphMetrics {
+                advance: 10.0,
+                ..Default::default()
+            },
+            _ => GlyphMetrics {
+                advance: 10.0,
+                left: 2.0,
+                top: 12.0,
+                width: 6.0,
+                height: 12.0,
+            },
+        }
+    }
+
+    fn pens(text: &str, x: f32, align: Align) -> Vec<f32> {
+        layout(text, x, 0.0, align, metrics)
+            .iter()
+            .map(|(ch, rect)| rect.pos.x - metrics(*ch).left)
+            .collect()
+    }
+
+    #[test]
+    fn width_is_the_sum_of_advances() {
+        assert_eq!(text_width("", metrics), 0.0);
+        assert_eq!(text_width("hi", metrics), 14.0);
+        assert_eq!(text_width("big a", metrics), 44.0);
+    }
+
+    #[test]
+    fn align_offsets_the_pen() {
+        assert_eq!(pens("hi", 100.0, Align::Left), [100.0, 110.0]);
+        assert_eq!(pens("hi", 100.0, Align::Center), [93.0, 103.0]);
+        assert_eq!(pens("hi", 100.0, Align::Right), [86.0, 96.0]);
+        assert!(layout("", 100.0, 0.0, Align::Center, metrics).is_empty());
+    }
+
+    #[test]
+    fn glyphs_are_placed_from_left_and_top() {
+        let glyphs: Vec<(char, Rect)> = layout("ig ", 50.0, 200.0, Align::Left, metrics);
+        let rect = |x: f32, y: f32, w: f32, h: f32| Rect::new(Vector::new(x, y), Vector::new(w, h));
+
+        assert_eq!(glyphs[0], ('i', rect(51.0, 188.0, 2.0, 12.0)));
+        // Descenders start above the baseline by `top` and reach below it
+        assert_eq!(glyphs[1], ('g', rect(55.0, 192.0, 8.0, 12.0)));
+        assert_eq!(glyphs[2], (' ', rect(64.0, 200.0, 0.0, 0.0)));
+    }
+}
@@ -0,0 +1,409 @@
+use crate::camera::Camera;
+use crate::color::ColorChannels;
+use crate::easing::Easing;
+use crate::properties::Properties;
+use crate::util::{field_property, parse_nums};
+use crate::vector::Vector;
+
+pub struct Trigger {
+    pub x: f32,
+    pub kind: TriggerKind,
+    pub group: u32,
+    pub spawn_triggered: bool,
+    pub activated: bool,
+}
+
+pub enum TriggerKind {
+    Move {
+        group: u32,
+        offset: Vector,
+        duration: f32,
+        easing: Easing,
+    },
+    Color {
+        channel: String,
+        color: [f32; 4],
+        duration: f32,
+    },
+    Toggle {
+        group: u32,
+        enable: bool,
+    },
+    Spawn {
+        group: u32,
+        delay: f32,
+    },
+    Collision {
+        block_a: u32,
+        block_b: u32,
+        group: u32,
+    },
+    Rotate {
+        group: u32,
+        center: u32,
+        degrees: f32,
+        duration: f32,
+        easing: Easing,
+    },
+    Alpha {
+        group: u32,
+        opacity: f32,
+        duration: f32,
+    },
+    CameraZoom {
+        zoom: f32,
+        duration: f32,
+        easing: Easing,
+    },
+    CameraOffset {
+        offset: Vector,
+        duration: f32,
+        easing: Easing,
+    },
+    // Locks onto `target`, or releases the lock back to following the player when `None`
+    CameraStatic {
+        target: Option<Vector>,
+        duration: f32,
+        easing: Easing,
+    },
+}
+
+pub struct Tween {
+    pub duration: f32,
+    pub easing: Easing,
+    pub elapsed: f32,
+}
+
+pub enum Action {
+    Move {
+        group: u32,
+        offset: Vector,
+        tween: Tween,
+    },
+    Color {
+        channel: String,
+        from: [f32; 4],
+        to: [f32; 4],
+        tween: Tween,
+    },
+    Toggle {
+        group: u32,
+        enable: bool,
+    },
+    Spawn {
+        group: u32,
+        delay: f32,
+    },
+    // Never finishes, fires its group every time the two collision block IDs start overlapping
+    Collision {
+        block_a: u32,
+        block_b: u32,
+        group: u32,
+        overlapping: bool,
+    },
+    Rotate {
+        group: u32,
+        center: u32,
+        degrees: f32,
+        tween: Tween,
+    },
+    Alpha {
+        group: u32,
+        opacity: f32,
+        tween: Tween,
+    },
+    CameraZoom {
+        from: f32,
+        zoom: f32,
+        tween: Tween,
+    },
+    CameraOffset {
+        offset: Vector,
+        tween: Tween,
+    },
+    CameraStatic {
+        from: f32,
+        target: Option<Vector>,
+        tween: Tween,
+    },
+}
+
+impl Trigger {
+    pub fn new(x: f32, kind: TriggerKind, group: u32, spawn_triggered: bool) -> Self {
+        Trigger {
+            x,
+            kind,
+            group,
+            spawn_triggered,
+            activated: false,
+        }
+    }
+
+    // Trigger on a level line of type 4 to 15, None for any other type. Easing names are
+    // optional and default to linear
+
+    pub fn parse(fields: &[&str], props: &Properties) -> Option<Self> {
+        let easing = |i: usize| Easing::from_name(fields.get(i).unwrap_or(&"linear"));
+        let (x, kind): (f32, TriggerKind) = match fields[0].parse::<u32>().unwrap() {
+            4 => {
+                // 4,x,group,dx,dy,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=5]);
+
+                (
+                    nums[0],
+                    TriggerKind::Move {
+                        group: nums[1] as u32,
+                        offset: Vector::new(nums[2], nums[3]),
+                        duration: nums[4],
+                        easing: easing(6),
+                    },
+                )
+            }
+            5 => {
+                // 5,x,channel,r,g,b,a,duration
+                let nums: Vec<f32> = parse_nums(&fields[3..=7]);
+
+                (
+                    fields[1].parse::<f32>().unwrap(),
+                    TriggerKind::Color {
+                        channel: fields[2].to_string(),
+                        color: [nums[0], nums[1], nums[2], nums[3]],
+                        duration: nums[4],
+                    },
+                )
+            }
+            6 => (
+                // 6,x,group,enable
+                fields[1].parse::<f32>().unwrap(),
+                TriggerKind::Toggle {
+                    group: fields[2].parse::<u32>().unwrap(),
+                    enable: fields[3].parse::<bool>().unwrap(),
+                },
+            ),
+            7 => (
+                // 7,x,group,delay
+                fields[1].parse::<f32>().unwrap(),
+                TriggerKind::Spawn {
+                    group: fields[2].parse::<u32>().unwrap(),
+                    delay: fields[3].parse::<f32>().unwrap(),
+                },
+            ),
+            9 => (
+                // 9,x,block_a,block_b,group
+                fields[1].parse::<f32>().unwrap(),
+                TriggerKind::Collision {
+                    block_a: fields[2].parse::<u32>().unwrap(),
+                    block_b: fields[3].parse::<u32>().unwrap(),
+                    group: fields[4].parse::<u32>().unwrap(),
+                },
+            ),
+            10 => {
+                // 10,x,group,center_group,degrees,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=5]);
+
+                (
+                    nums[0],
+                    TriggerKind::Rotate {
+                        group: nums[1] as u32,
+                        center: nums[2] as u32,
+                        degrees: nums[3],
+                        duration: nums[4],
+                        easing: easing(6),
+                    },
+                )
+            }
+            11 => {
+                // 11,x,group,opacity,duration
+                let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+                (
+                    nums[0],
+                    TriggerKind::Alpha {
+                        group: nums[1] as u32,
+                        opacity: nums[2],
+                        duration: nums[3],
+                    },
+                )
+            }
+            12 => {
+                // 12,x,zoom,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=3]);
+
+                (
+                    nums[0],
+                    TriggerKind::CameraZoom {
+                        zoom: nums[1],
+                        duration: nums[2],
+                        easing: easing(4),
+                    },
+                )
+            }
+            13 => {
+                // 13,x,dx,dy,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+                (
+                    nums[0],
+                    TriggerKind::CameraOffset {
+                        offset: Vector::new(nums[1], nums[2]),
+                        duration: nums[3],
+                        easing: easing(5),
+                    },
+                )
+            }
+            14 => {
+                // 14,x,target_x,target_y,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=4]);
+
+                (
+                    nums[0],
+                    TriggerKind::CameraStatic {
+                        target: Some(Vector::new(nums[1], nums[2])),
+                        duration: nums[3],
+                        easing: easing(5),
+                    },
+                )
+            }
+            15 => {
+                // 15,x,duration,easing
+                let nums: Vec<f32> = parse_nums(&fields[1..=2]);
+
+                (
+                    nums[0],
+                    TriggerKind::CameraStatic {
+                        target: None,
+                        duration: nums[1],
+                        easing: easing(3),
+                    },
+                )
+            }
+            _ => return None,
+        };
+        let spawn_triggered: bool = field_property(fields, "spawn")
+            .map(|x| x.parse::<bool>().unwrap())
+            .unwrap_or(false);
+
+        Some(Trigger::new(x, kind, props.group, spawn_triggered))
+    }
+
+    pub fn action(&self, colors: &ColorChannels, camera: &Camera) -> Action {
+        match &self.kind {
+            TriggerKind::Move {
+                group,
+                offset,
+                duration,
+                easing,
+            } => Action::Move {
+                group: *group,
+                offset: *offset,
+                tween: Tween::new(*duration, *easing),
+            },
+            TriggerKind::Color {
+                channel,
+                color,
+                duration,
+            } => Action::Color {
+                channel: channel.clone(),
+                from: colors.get(channel),
+                to: *color,
+                tween: Tween::new(*duration, Easing::Linear),
+            },
+            TriggerKind::Toggle { group, enable } => Action::Toggle {
+                group: *group,
+                enable: *enable,
+            },
+            TriggerKind::Spawn { group, delay } => Action::Spawn {
+                group: *group,
+                delay: *delay,
+            },
+            TriggerKind::Collision {
+                block_a,
+                block_b,
+                group,
+            } => Action::Collision {
+                block_a: *block_a,
+                block_b: *block_b,
+                group: *group,
+                overlapping: false,
+            },
+            TriggerKind::Rotate {
+                group,
+                center,
+                degrees,
+                duration,
+                easing,
+            } => Action::Rotate {
+                group: *group,
+                center: *center,
+                degrees: *degrees,
+                tween: Tween::new(*duration, *easing),
+            },
+            TriggerKind::Alpha {
+                group,
+                opacity,
+                duration,
+            } => Action::Alpha {
+                group: *group,
+                opacity: *opacity,
+                tween: Tween::new(*duration, Easing::Linear),
+            },
+            TriggerKind::CameraZoom {
+                zoom,
+                duration,
+                easing,
+            } => Action::CameraZoom {
+                from: camera.zoom,
+                zoom: *zoom,
+                tween: Tween::new(*duration, *easing),
+            },
+            TriggerKind::CameraOffset {
+                offset,
+                duration,
+                easing,
+            } => Action::CameraOffset {
+                offset: *offset,
+                tween: Tween::new(*duration, *easing),
+            },
+            TriggerKind::CameraStatic {
+                target,
+                duration,
+                easing,
+            } => Action::CameraStatic {
+                from: camera.lock_blend,
+                target: *target,
+                tween: Tween::new(*duration, *easing),
+            },
+        }
+    }
+}
+
+impl Tween {
+    pub fn new(duration: f32, easing: Easing) -> Self {
+        Tween {
+            duration,
+            easing,
+            elapsed: 0.0,
+        }
+    }
+
+    // Returns the eased progress before and after this step, so callers can apply the difference
+
+    pub fn advance(&mut self, dt: f32) -> (f32, f32) {
+        let before: f32 = self.progress();
+        self.elapsed += dt;
+
+        (before, self.progress())
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            self.easing.apply(self.elapsed / self.duration)
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
@@ -1,81 +1,147 @@
+use crate::rect::Rect;
+use crate::segment::Segment;
 use crate::vector::Vector;
 
 // http://jeffreythompson.org/collision-detection/poly-rect.php
 
-pub fn line_line(
-    line_a_1: &Vector,
-    line_a_2: &Vector,
-    line_b_1: &Vector,
-    line_b_2: &Vector,
-) -> bool {
-    let ua: f32 = ((line_b_2.x - line_b_1.x) * (line_a_1.y - line_b_1.y)
-        - (line_b_2.y - line_b_1.y) * (line_a_1.x - line_b_1.x))
-        / ((line_b_2.y - line_b_1.y) * (line_a_2.x - line_a_1.x)
-            - (line_b_2.x - line_b_1.x) * (line_a_2.y - line_a_1.y));
-    let ub: f32 = ((line_a_2.x - line_a_1.x) * (line_a_1.y - line_b_1.y)
-        - (line_a_2.y - line_a_1.y) * (line_a_1.x - line_b_1.x))
-        / ((line_b_2.y - line_b_1.y) * (line_a_2.x - line_a_1.x)
-            - (line_b_2.x - line_b_1.x) * (line_a_2.y - line_a_1.y));
-
-    (0.0..=1.0).contains(&ua) && (0.0..=1.0).contains(&ub)
+pub fn polygon_point(vertices: &[[f64; 2]], point: Vector) -> bool {
+    let mut collision: bool = false;
+
+    for (&current, &next) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+        let vc: Vector = Vector::from_vertex(current);
+        let vn: Vector = Vector::from_vertex(next);
+
+        if ((vc.y > point.y && vn.y < point.y) || (vc.y < point.y && vn.y > point.y))
+            && point.x < (vn.x - vc.x) * (point.y - vc.y) / (vn.y - vc.y) + vc.x
+        {
+            collision = !collision
+        }
+    }
+
+    collision
 }
 
-pub fn line_rect(line_1: &Vector, line_2: &Vector, rect_pos: &Vector, rect_size: &Vector) -> bool {
-    let left: bool = line_line(
-        line_1,
-        line_2,
-        rect_pos,
-        &Vector::new(rect_pos.x, rect_pos.y + rect_size.y),
-    );
-    let right: bool = line_line(
-        line_1,
-        line_2,
-        &Vector::new(rect_pos.x + rect_size.x, rect_pos.y),
-        &Vector::new(rect_pos.x + rect_size.x, rect_pos.y + rect_size.y),
-    );
-    let top: bool = line_line(
-        line_1,
-        line_2,
-        rect_pos,
-        &Vector::new(rect_pos.x + rect_size.x, rect_pos.y),
-    );
-    let bottom: bool = line_line(
-        line_1,
-        line_2,
-        &Vector::new(rect_pos.x, rect_pos.y + rect_size.y),
-        &Vector::new(rect_pos.x + rect_size.x, rect_pos.y + rect_size.y),
-    );
-
-    left || right || top || bottom
+pub fn polygon_rect(vertices: &[[f64; 2]], rect: &Rect) -> bool {
+    let crossing: bool =
+        vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .any(|(&current, &next)| {
+                Segment::new(Vector::from_vertex(current), Vector::from_vertex(next))
+                    .intersects_rect(rect)
+            });
+
+    // Edges that only touch along a shared line never register as crossing, so test every
+    // corner and vertex for containment as well
+
+    crossing
+        || rect
+            .corners()
+            .into_iter()
+            .any(|corner| polygon_point(vertices, corner))
+        || vertices
+            .iter()
+            .any(|&v| rect.contains(Vector::from_vertex(v)))
 }
 
-pub fn polygon_point(vertices: [[f64; 2]; 3], point: Vector) -> bool {
-    let mut collision: bool = false;
-    let mut next: usize;
+// Numeric fields of a level line, like `&fields[1..=4]` for a block's position and size
 
-    let mut current: usize = 0;
-    while current < vertices.len() {
-        next = current + 1;
+pub fn parse_nums(fields: &[&str]) -> Vec<f32> {
+    fields.iter().map(|x| x.parse::<f32>().unwrap()).collect()
+}
+
+// Optional trailing `key=value` fields on a level line, e.g. `1,1260,855,378,63,group=2`
 
-        if next == vertices.len() {
-            next = 0
+pub fn field_property<'a>(fields: &[&'a str], key: &str) -> Option<&'a str> {
+    fields.iter().find_map(|field| {
+        let (k, v) = field.split_once('=')?;
+
+        if k.trim() == key {
+            Some(v.trim())
+        } else {
+            None
         }
+    })
+}
 
-        let vc: [f64; 2] = vertices[current];
-        let vn: [f64; 2] = vertices[next];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if ((vc[1] as f32 > point.y && (vn[1] as f32) < point.y)
-            || ((vc[1] as f32) < point.y && vn[1] as f32 > point.y))
-            && (point.x
-                < (vn[0] as f32 - vc[0] as f32) * (point.y - vc[1] as f32)
-                    / (vn[1] as f32 - vc[1] as f32)
-                    + vc[0] as f32)
-        {
-            collision = !collision
+    // Small triangle around `center`, pointing up
+
+    fn triangle(center: Vector) -> [[f64; 2]; 3] {
+        [
+            Vector::new(center.x - 10.0, center.y + 8.0).to_vertex(),
+            Vector::new(center.x + 10.0, center.y + 8.0).to_vertex(),
+            Vector::new(center.x, center.y - 12.0).to_vertex(),
+        ]
+    }
+
+    fn player() -> Rect {
+        Rect::new(Vector::new(100.0, 100.0), Vector::new(50.0, 50.0))
+    }
+
+    #[test]
+    fn polygon_rect_hits_every_corner() {
+        let rect: Rect = player();
+        let outward: [Vector; 4] = [
+            Vector::new(-1.0, -1.0),
+            Vector::new(1.0, -1.0),
+            Vector::new(1.0, 1.0),
+            Vector::new(-1.0, 1.0),
+        ];
+
+        for (corner, outward) in rect.corners().into_iter().zip(outward) {
+            assert!(
+                polygon_rect(&triangle(corner), &rect),
+                "corner {:?}",
+                corner
+            );
+            assert!(
+                !polygon_rect(&triangle(corner + outward * 20.0), &rect),
+                "past corner {:?}",
+                corner
+            );
         }
+    }
+
+    #[test]
+    fn polygon_rect_hits_every_edge() {
+        let rect: Rect = player();
+        let outward: [Vector; 4] = [
+            Vector::new(0.0, -1.0),
+            Vector::new(1.0, 0.0),
+            Vector::new(0.0, 1.0),
+            Vector::new(-1.0, 0.0),
+        ];
 
-        current += 1;
+        for (edge, outward) in rect.edges().into_iter().zip(outward) {
+            let middle: Vector = (edge.a + edge.b) / 2.0;
+
+            assert!(polygon_rect(&triangle(middle), &rect), "edge {:?}", edge);
+            assert!(
+                !polygon_rect(&triangle(middle + outward * 20.0), &rect),
+                "past edge {:?}",
+                edge
+            );
+        }
     }
 
-    collision
+    #[test]
+    fn polygon_rect_hits_without_crossing_edges() {
+        let rect: Rect = player();
+
+        // Spike entirely inside the player, then the player entirely inside a huge spike
+        assert!(polygon_rect(&triangle(rect.center()), &rect));
+        assert!(polygon_rect(
+            &[[0.0, 400.0], [250.0, 400.0], [125.0, 0.0]],
+            &rect
+        ));
+        // Tip resting on the top edge
+        assert!(polygon_rect(
+            &[[115.0, 80.0], [135.0, 80.0], [125.0, 100.0]],
+            &rect
+        ));
+    }
 }
@@ -1,4 +1,6 @@
-#[derive(PartialEq)]
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Vector {
     pub x: f32,
     pub y: f32,
@@ -9,9 +11,202 @@ impl Vector {
         Vector { x, y }
     }
 
-    pub fn dist(&self, other: &Vector) -> f32 {
-        let dx: f32 = self.x - other.x;
-        let dy: f32 = self.y - other.y;
-        (dx.powi(2) + dy.powi(2)).sqrt()
+    // Vertices are stored as `[f64; 2]` for the graphics crate
+
+    pub fn from_vertex(vertex: [f64; 2]) -> Self {
+        Vector::new(vertex[0] as f32, vertex[1] as f32)
+    }
+
+    pub fn to_vertex(self) -> [f64; 2] {
+        [self.x as f64, self.y as f64]
+    }
+
+    pub fn dot(self, other: Vector) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // z component of the 3D cross product, positive when `other` is clockwise from `self` on
+    // screen since y points down
+
+    pub fn cross(self, other: Vector) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn dist(self, other: Vector) -> f32 {
+        (self - other).length()
+    }
+
+    // The zero vector has no direction and stays zero
+
+    pub fn normalize(self) -> Vector {
+        let length: f32 = self.length();
+
+        if length == 0.0 {
+            self
+        } else {
+            self / length
+        }
+    }
+
+    pub fn lerp(self, other: Vector, t: f32) -> Vector {
+        self + (other - self) * t
+    }
+
+    // Rotation by `degrees` around the origin, clockwise on screen
+
+    pub fn rotate(self, degrees: f32) -> Vector {
+        let (sin, cos): (f32, f32) = degrees.to_radians().sin_cos();
+
+        Vector::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    pub fn rotate_about(self, pivot: Vector, degrees: f32) -> Vector {
+        pivot + (self - pivot).rotate(degrees)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, scale: f32) -> Vector {
+        Vector::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Div<f32> for Vector {
+    type Output = Vector;
+
+    fn div(self, scale: f32) -> Vector {
+        Vector::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, other: Vector) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vector {
+    fn mul_assign(&mut self, scale: f32) {
+        *self = *self * scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!(a.dist(b) < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn operators() {
+        let a: Vector = Vector::new(3.0, -4.0);
+        let b: Vector = Vector::new(1.0, 2.0);
+
+        assert_eq!(a + b, Vector::new(4.0, -2.0));
+        assert_eq!(a - b, Vector::new(2.0, -6.0));
+        assert_eq!(a * 2.0, Vector::new(6.0, -8.0));
+        assert_eq!(a / 2.0, Vector::new(1.5, -2.0));
+        assert_eq!(-a, Vector::new(-3.0, 4.0));
+
+        let mut c: Vector = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= b;
+        assert_eq!(c, a);
+        c *= 3.0;
+        assert_eq!(c, a * 3.0);
+    }
+
+    #[test]
+    fn dot_and_cross() {
+        let right: Vector = Vector::new(1.0, 0.0);
+        let down: Vector = Vector::new(0.0, 1.0);
+
+        assert_eq!(right.dot(down), 0.0);
+        assert_eq!(Vector::new(3.0, -4.0).dot(Vector::new(1.0, 2.0)), -5.0);
+        // Down is clockwise from right on screen
+        assert_eq!(right.cross(down), 1.0);
+        assert_eq!(down.cross(right), -1.0);
+        assert_eq!(right.cross(right * 5.0), 0.0);
+    }
+
+    #[test]
+    fn length_and_normalize() {
+        let a: Vector = Vector::new(3.0, -4.0);
+
+        assert_eq!(a.length(), 5.0);
+        assert_eq!(a.dist(Vector::default()), 5.0);
+        assert_close(a.normalize(), Vector::new(0.6, -0.8));
+        assert_eq!(Vector::default().normalize(), Vector::default());
+    }
+
+    #[test]
+    fn lerp() {
+        let a: Vector = Vector::new(0.0, 10.0);
+        let b: Vector = Vector::new(10.0, 20.0);
+
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.25), Vector::new(2.5, 12.5));
+    }
+
+    #[test]
+    fn rotate() {
+        let right: Vector = Vector::new(1.0, 0.0);
+
+        // Clockwise on screen, so right turns to down
+        assert_close(right.rotate(90.0), Vector::new(0.0, 1.0));
+        assert_close(right.rotate(180.0), Vector::new(-1.0, 0.0));
+        assert_close(right.rotate(-90.0), Vector::new(0.0, -1.0));
+        assert_close(
+            Vector::new(2.0, 1.0).rotate_about(Vector::new(1.0, 1.0), 90.0),
+            Vector::new(1.0, 2.0),
+        );
+        assert_close(
+            Vector::new(5.0, 5.0).rotate_about(Vector::new(5.0, 5.0), 45.0),
+            Vector::new(5.0, 5.0),
+        );
     }
 }